@@ -0,0 +1,50 @@
+//! Generates the code registering the solver of every day crate the CLI depends on. Adding a
+//! new day only requires adding its crate to the dependencies in `Cargo.toml`.
+
+use std::env;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let manifest_path = Path::new(&manifest_dir).join("Cargo.toml");
+    println!("cargo:rerun-if-changed={}", manifest_path.display());
+
+    let mut manifest = String::new();
+    File::open(&manifest_path).unwrap().read_to_string(&mut manifest).unwrap();
+    let day_crates = day_crates(&manifest);
+
+    let mut code = String::new();
+    for name in &day_crates {
+        code.push_str(&format!("extern crate {};\n", name));
+    }
+    code.push_str("\n/// Registers the solvers of all day crates.\n");
+    code.push_str("fn register_solvers(registry: &mut ::base::Registry) {\n");
+    for name in &day_crates {
+        code.push_str(&format!("    {}::register(registry);\n", name));
+    }
+    code.push_str("}\n");
+
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("solvers.rs");
+    File::create(out_path).unwrap().write_all(code.as_bytes()).unwrap();
+}
+
+/// Returns the names of all dependencies called `day` followed by a number.
+fn day_crates(manifest: &str) -> Vec<String> {
+    let mut in_dependencies = false;
+    let mut crates = vec![];
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_dependencies = line == "[dependencies]";
+            continue;
+        }
+        let name = line.split('=').next().unwrap_or("").trim();
+        let is_day = name.len() > 3 && name.starts_with("day") &&
+                     name[3..].chars().all(|c| c.is_ascii_digit());
+        if in_dependencies && is_day {
+            crates.push(name.to_owned());
+        }
+    }
+    crates
+}
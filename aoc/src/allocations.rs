@@ -0,0 +1,186 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use sandbox::format_size;
+
+/// Whether allocations are counted. Off by default, so the allocator only costs one atomic load
+/// per allocation.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The allocation counters of one thread. Counted per thread so solvers running at the same
+/// time don't count each other's allocations.
+#[derive(Debug, Clone, Copy)]
+struct Counters {
+    allocations: u64,
+    bytes: u64,
+    /// Bytes allocated and not yet freed by this thread. Can go below zero when memory allocated
+    /// by another thread is freed.
+    live: i64,
+    peak: i64,
+}
+
+thread_local! {
+    static COUNTERS: Cell<Counters> = const {
+        Cell::new(Counters {
+            allocations: 0,
+            bytes: 0,
+            live: 0,
+            peak: 0,
+        })
+    };
+}
+
+/// The system allocator, counting the allocations of every thread once `enable` is called.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record(layout.size() as i64, layout.size() as i64);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record(layout.size() as i64, layout.size() as i64);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record(0, -(layout.size() as i64));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record(new_size as i64, new_size as i64 - layout.size() as i64);
+        }
+        new_ptr
+    }
+}
+
+/// Counts an allocation of `allocated` bytes, or a deallocation if it is zero, that changed the
+/// live bytes by `live_change`.
+fn record(allocated: i64, live_change: i64) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    // Fails if the thread is being torn down, then the allocation is not counted.
+    let _ = COUNTERS.try_with(|counters| {
+        let mut c = counters.get();
+        if allocated > 0 {
+            c.allocations += 1;
+            c.bytes += allocated as u64;
+        }
+        c.live += live_change;
+        c.peak = c.peak.max(c.live);
+        counters.set(c);
+    });
+}
+
+/// Starts counting allocations.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// The allocations made while running some code.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct AllocStats {
+    /// How many times memory was allocated or reallocated.
+    pub allocations: u64,
+    /// The total size of all allocations.
+    pub bytes: u64,
+    /// The most bytes that were allocated and not yet freed at the same time, not counting what
+    /// was already allocated when the code started.
+    pub peak_bytes: u64,
+}
+
+impl fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+               "{} allocations, {} allocated, {} peak",
+               self.allocations,
+               format_size(self.bytes),
+               format_size(self.peak_bytes))
+    }
+}
+
+/// Runs `f` and counts the allocations it makes on the current thread. The stats are `None` if
+/// counting is not enabled.
+pub fn measure<F: FnOnce() -> T, T>(f: F) -> (T, Option<AllocStats>) {
+    if !is_enabled() {
+        return (f(), None);
+    }
+    let start = COUNTERS.with(|counters| {
+        let mut c = counters.get();
+        c.peak = c.live;
+        counters.set(c);
+        c
+    });
+    let result = f();
+    let end = COUNTERS.with(|counters| counters.get());
+    let stats = AllocStats {
+        allocations: end.allocations - start.allocations,
+        bytes: end.bytes - start.bytes,
+        peak_bytes: (end.peak - start.live).max(0) as u64,
+    };
+    (result, Some(stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{enable, measure, AllocStats};
+
+    #[test]
+    fn counts_allocations() {
+        enable();
+        let (_, stats) = measure(|| {
+            let mut v: Vec<u64> = Vec::with_capacity(100);
+            v.push(1);
+            let w: Vec<u8> = vec![0; 1000];
+            drop(w);
+            v
+        });
+        let stats = stats.unwrap();
+        assert_eq!(2, stats.allocations);
+        assert_eq!(1800, stats.bytes);
+        assert_eq!(1800, stats.peak_bytes);
+    }
+
+    #[test]
+    fn peak_is_relative_to_start() {
+        enable();
+        let _before = vec![0u8; 4096];
+        let ((), stats) = measure(|| {
+            drop(vec![0u8; 100]);
+            drop(vec![0u8; 200]);
+        });
+        assert_eq!(Some(AllocStats {
+                       allocations: 2,
+                       bytes: 300,
+                       peak_bytes: 200,
+                   }),
+                   stats);
+    }
+
+    #[test]
+    fn display() {
+        let stats = AllocStats {
+            allocations: 3,
+            bytes: 2048,
+            peak_bytes: 1024,
+        };
+        assert_eq!("3 allocations, 2.0 KiB allocated, 1.0 KiB peak", stats.to_string());
+    }
+}
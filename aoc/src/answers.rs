@@ -0,0 +1,302 @@
+use base::{Part, Solution};
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+/// The expected answers for a set of (day, part, input) combinations.
+///
+/// Stored on disk as a plain text file with one answer per line in the format
+/// `<day> <part> <input path> <type> <answer>`, where the type is `int`, `uint`, `text` or
+/// `lines`. Empty lines and lines starting with `#` are ignored. Backslashes and newlines in
+/// answers are escaped so every answer fits on one line, and spaces in input paths are escaped
+/// as `\s` so they don't end the field.
+///
+/// Input paths are stored relative to the directory of the answers file, so the same input
+/// has the same key whatever directory the answers are recorded or verified from. Inputs outside
+/// that directory are stored with their absolute path.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct AnswerStore {
+    answers: BTreeMap<(u8, Part, String), Solution>,
+    /// The directory input paths are relative to. Empty for the current directory.
+    base_dir: PathBuf,
+}
+
+impl AnswerStore {
+    /// Loads the store from the given file. A file that does not exist gives an empty store.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let mut data = String::new();
+        match File::open(path) {
+            Ok(mut f) => {
+                f.read_to_string(&mut data)
+                    .map_err(|e| format!("Unable to read {}: {}", path.display(), e))?;
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(format!("Unable to open {}: {}", path.display(), e)),
+        };
+        let mut store = Self::parse(&data)
+            .map_err(|e| format!("Invalid answers file {}: {}", path.display(), e))?;
+        store.base_dir = absolute_path(path.parent().unwrap_or_else(|| Path::new("")));
+        Ok(store)
+    }
+
+    pub fn parse(data: &str) -> Result<Self, String> {
+        let mut store = Self::default();
+        for (i, line) in data.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.splitn(4, ' ');
+            let mut next_field = |name| {
+                fields.next().ok_or_else(|| format!("Missing {} on line {}", name, i + 1))
+            };
+            let day = u8::from_str(next_field("day")?)
+                .map_err(|e| format!("Invalid day on line {}: {}", i + 1, e))?;
+            let part = Part::from_str(next_field("part")?)
+                .map_err(|e| format!("{} on line {}", e, i + 1))?;
+            let input_path = unescape(next_field("input path")?);
+            let answer = decode_solution(next_field("answer")?)
+                .map_err(|e| format!("{} on line {}", e, i + 1))?;
+            store.answers.insert((day, part, input_path), answer);
+        }
+        Ok(store)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut f = File::create(path)?;
+        f.write_all(self.to_string().as_bytes())
+    }
+
+    /// Returns the expected answer for the given day, part and input, if one is recorded. The
+    /// input path is relative to the current directory.
+    pub fn get(&self, day: u8, part: Part, input_path: &str) -> Option<&Solution> {
+        self.answers.get(&(day, part, self.key(input_path)))
+    }
+
+    pub fn insert(&mut self, day: u8, part: Part, input_path: &str, answer: Solution) {
+        let key = self.key(input_path);
+        self.answers.insert((day, part, key), answer);
+    }
+
+    /// The key of an input path relative to the current directory. Different spellings of the
+    /// same path, like `./inputs/1` and `inputs/1`, give the same key. Stdin is kept as `-`.
+    fn key(&self, input_path: &str) -> String {
+        if input_path == "-" {
+            return input_path.to_owned();
+        }
+        let path = absolute_path(Path::new(input_path));
+        match path.strip_prefix(absolute_path(&self.base_dir)) {
+            Ok(relative) => {
+                let components: Vec<_> = relative.components()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .collect();
+                components.join("/")
+            }
+            Err(_) => path.to_string_lossy().into_owned(),
+        }
+    }
+}
+
+impl ::std::fmt::Display for AnswerStore {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        writeln!(f, "# day part input type answer")?;
+        for (&(day, part, ref input_path), answer) in &self.answers {
+            writeln!(f,
+                     "{} {} {} {}",
+                     day,
+                     part,
+                     escape_field(input_path),
+                     encode_solution(answer))?;
+        }
+        Ok(())
+    }
+}
+
+/// Makes the path absolute by joining it to the current directory, and removes `.` and `..`
+/// without looking at the file system. Returns the path as it is if there is no current
+/// directory.
+fn absolute_path(path: &Path) -> PathBuf {
+    let path = match env::current_dir() {
+        Ok(current_dir) => current_dir.join(path),
+        Err(_) => path.to_owned(),
+    };
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Writes the type of the solution, `int`, `uint`, `text` or `lines`, followed by a space and
+/// the escaped answer, so `decode_solution` gives back the same variant. A text answer that
+/// reads like a number stays text.
+pub fn encode_solution(solution: &Solution) -> String {
+    let kind = match *solution {
+        Solution::Int(_) => "int",
+        Solution::UInt(_) => "uint",
+        Solution::Text(_) => "text",
+        Solution::Lines(_) => "lines",
+    };
+    format!("{} {}", kind, escape(&solution.to_string()))
+}
+
+/// Reverses `encode_solution`.
+pub fn decode_solution(s: &str) -> Result<Solution, String> {
+    let (kind, value) = s.split_once(' ').unwrap_or((s, ""));
+    let value = unescape(value);
+    match kind {
+        "int" => {
+            i64::from_str(&value)
+                .map(Solution::Int)
+                .map_err(|e| format!("Invalid int answer {:?}: {}", value, e))
+        }
+        "uint" => {
+            u64::from_str(&value)
+                .map(Solution::UInt)
+                .map_err(|e| format!("Invalid uint answer {:?}: {}", value, e))
+        }
+        "text" => Ok(Solution::Text(value)),
+        "lines" => Ok(Solution::Lines(value.split('\n').map(str::to_owned).collect())),
+        kind => Err(format!("Unknown answer type {:?}, expected int, uint, text or lines", kind)),
+    }
+}
+
+/// Escapes backslashes and newlines so the answer fits on one line.
+pub fn escape(answer: &str) -> String {
+    answer.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Escapes like `escape` and also escapes spaces, for a field followed by more fields.
+fn escape_field(field: &str) -> String {
+    escape(field).replace(' ', "\\s")
+}
+
+/// Reverses `escape` and `escape_field`.
+pub fn unescape(answer: &str) -> String {
+    let mut result = String::with_capacity(answer.len());
+    let mut chars = answer.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                result.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                result.push('\\');
+                chars.next();
+            }
+            ('\\', Some('s')) => {
+                result.push(' ');
+                chars.next();
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use base::{Part, Solution};
+    use std::env;
+    use super::{decode_solution, encode_solution, AnswerStore};
+
+    #[test]
+    fn parse_and_get() {
+        let store =
+            AnswerStore::parse("# comment\n\n1 1 inputs/1 uint 301\n2 2 inputs/2 text 9A7DC\n")
+                .unwrap();
+        assert_eq!(Some(&Solution::UInt(301)), store.get(1, Part::One, "inputs/1"));
+        assert_eq!(Some(&Solution::from("9A7DC")), store.get(2, Part::Two, "./inputs/2"));
+        assert_eq!(None, store.get(1, Part::Two, "inputs/1"));
+    }
+
+    #[test]
+    fn parse_invalid_part() {
+        assert!(AnswerStore::parse("1 3 inputs/1 uint 301").is_err());
+    }
+
+    #[test]
+    fn parse_missing_answer() {
+        assert!(AnswerStore::parse("1 1 inputs/1").is_err());
+    }
+
+    #[test]
+    fn parse_invalid_type() {
+        let error = AnswerStore::parse("1 1 inputs/1 301").unwrap_err();
+        assert_eq!("Unknown answer type \"301\", expected int, uint, text or lines on line 1",
+                   error);
+        assert!(AnswerStore::parse("1 1 inputs/1 uint -4").is_err());
+    }
+
+    #[test]
+    fn numeric_text_keeps_its_type() {
+        let store = AnswerStore::parse("2 1 inputs/2 text 97289\n").unwrap();
+        let answer = store.get(2, Part::One, "inputs/2").unwrap();
+        assert!(matches!(*answer, Solution::Text(ref code) if code == "97289"));
+        assert_ne!(&Solution::UInt(97289), answer);
+    }
+
+    #[test]
+    fn solution_round_trip() {
+        let solutions = vec![Solution::Int(-4),
+                             Solution::UInt(u64::MAX),
+                             Solution::Text("97289".to_owned()),
+                             Solution::Text("".to_owned()),
+                             Solution::Text("a\\b\nc".to_owned()),
+                             Solution::Lines(vec!["#..#".to_owned(),
+                                                  "".to_owned(),
+                                                  "\\".to_owned()])];
+        for solution in solutions {
+            let decoded = decode_solution(&encode_solution(&solution)).unwrap();
+            assert_eq!(format!("{:?}", solution), format!("{:?}", decoded));
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut store = AnswerStore::default();
+        store.insert(1, Part::One, "inputs/1", Solution::UInt(301));
+        store.insert(1, Part::Two, "inputs/1", Solution::Int(-4));
+        store.insert(2, Part::One, "inputs/2", Solution::from("two words"));
+        let lines = vec!["#..#".to_owned(), "a \\ line".to_owned()];
+        store.insert(3, Part::Two, "inputs/3", Solution::Lines(lines));
+        let parsed = AnswerStore::parse(&store.to_string()).unwrap();
+        assert_eq!(store, parsed);
+    }
+
+    #[test]
+    fn input_paths_relative_to_answers_dir() {
+        let mut store = AnswerStore::default();
+        store.insert(1, Part::One, "inputs/../inputs/./1", Solution::UInt(301));
+        assert_eq!(Some(&Solution::UInt(301)), store.get(1, Part::One, "inputs/1"));
+        let absolute = env::current_dir().unwrap().join("inputs/1");
+        assert_eq!(Some(&Solution::UInt(301)),
+                   store.get(1, Part::One, absolute.to_str().unwrap()));
+        assert!(store.to_string().contains("1 1 inputs/1 uint 301"));
+        // Inputs outside the directory keep their absolute path.
+        store.insert(2, Part::One, "/elsewhere/2", Solution::from("9A7DC"));
+        assert!(store.to_string().contains("2 1 /elsewhere/2 text 9A7DC"));
+    }
+
+    #[test]
+    fn input_path_with_spaces() {
+        let mut store = AnswerStore::default();
+        store.insert(1, Part::One, "my inputs/day 1\\s", Solution::UInt(301));
+        let data = store.to_string();
+        assert!(data.contains("1 1 my\\sinputs/day\\s1\\\\s uint 301"), "{}", data);
+        let parsed = AnswerStore::parse(&data).unwrap();
+        assert_eq!(Some(&Solution::UInt(301)),
+                   parsed.get(1, Part::One, "my inputs/day 1\\s"));
+    }
+}
@@ -0,0 +1,298 @@
+use base::Params;
+use bench::{Stage, Stats};
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Benchmark results saved under a name, so later runs can be compared against them.
+///
+/// Stored on disk as a plain text file with one benchmark per line in the format
+/// `<day> <stage> <input hash> <params hash> <median> <mean> <std dev> <min> <max> <samples>
+/// <outliers> <iterations per sample>`. Times are in nanoseconds. Empty lines and lines starting
+/// with `#` are ignored.
+#[derive(Debug, Default, PartialEq)]
+pub struct Baseline {
+    results: BTreeMap<(u8, Stage, u64, u64), Stats>,
+}
+
+impl Baseline {
+    /// Loads the baseline with the given name from the given directory. A baseline that does
+    /// not exist gives an empty baseline.
+    pub fn load(dir: &str, name: &str) -> Result<Self, String> {
+        let path = baseline_path(dir, name);
+        let mut data = String::new();
+        match File::open(&path) {
+            Ok(mut f) => {
+                f.read_to_string(&mut data)
+                    .map_err(|e| format!("Unable to read {}: {}", path.display(), e))?
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Unable to open {}: {}", path.display(), e)),
+        };
+        Self::parse(&data).map_err(|e| format!("Invalid baseline {}: {}", path.display(), e))
+    }
+
+    pub fn parse(data: &str) -> Result<Self, String> {
+        let mut baseline = Self::default();
+        for (i, line) in data.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 12 {
+                return Err(format!("Expected 12 fields on line {}, found {}",
+                                   i + 1,
+                                   fields.len()));
+            }
+            let invalid = |name| format!("Invalid {} on line {}", name, i + 1);
+            let day = u8::from_str(fields[0]).map_err(|_| invalid("day"))?;
+            let stage = Stage::from_str(fields[1]).map_err(|_| invalid("stage"))?;
+            let input_hash = u64::from_str_radix(fields[2], 16).map_err(|_| invalid("hash"))?;
+            let params_hash =
+                u64::from_str_radix(fields[3], 16).map_err(|_| invalid("params hash"))?;
+            let mut times = [0.0; 5];
+            for (time, field) in times.iter_mut().zip(&fields[4..9]) {
+                *time = f64::from_str(field).map_err(|_| invalid("time"))?;
+            }
+            let stats = Stats {
+                median: times[0],
+                mean: times[1],
+                std_dev: times[2],
+                min: times[3],
+                max: times[4],
+                samples: usize::from_str(fields[9]).map_err(|_| invalid("sample count"))?,
+                outliers: usize::from_str(fields[10]).map_err(|_| invalid("outlier count"))?,
+                iterations_per_sample: u64::from_str(fields[11])
+                    .map_err(|_| invalid("iteration count"))?,
+            };
+            baseline.results.insert((day, stage, input_hash, params_hash), stats);
+        }
+        Ok(baseline)
+    }
+
+    /// Saves the baseline under the given name in the given directory, creating the directory
+    /// if needed.
+    pub fn save(&self, dir: &str, name: &str) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let mut f = File::create(baseline_path(dir, name))?;
+        f.write_all(self.to_string().as_bytes())
+    }
+
+    pub fn get(&self, day: u8, stage: Stage, input_hash: u64, params_hash: u64) -> Option<&Stats> {
+        self.results.get(&(day, stage, input_hash, params_hash))
+    }
+
+    pub fn insert(&mut self,
+                  day: u8,
+                  stage: Stage,
+                  input_hash: u64,
+                  params_hash: u64,
+                  stats: Stats) {
+        self.results.insert((day, stage, input_hash, params_hash), stats);
+    }
+}
+
+impl fmt::Display for Baseline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f,
+                 "# day stage input_hash params_hash median mean std_dev min max samples \
+                  outliers iterations_per_sample")?;
+        for (&(day, stage, input_hash, params_hash), stats) in &self.results {
+            writeln!(f,
+                     "{} {} {:016x} {:016x} {} {} {} {} {} {} {} {}",
+                     day,
+                     stage,
+                     input_hash,
+                     params_hash,
+                     stats.median,
+                     stats.mean,
+                     stats.std_dev,
+                     stats.min,
+                     stats.max,
+                     stats.samples,
+                     stats.outliers,
+                     stats.iterations_per_sample)?;
+        }
+        Ok(())
+    }
+}
+
+fn baseline_path(dir: &str, name: &str) -> PathBuf {
+    Path::new(dir).join(name)
+}
+
+/// How a benchmark result compares to the baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    /// Slower than the baseline by more than the noise threshold. Holds the change in percent.
+    Regression(f64),
+    /// Faster than the baseline by more than the noise threshold. Holds the change in percent.
+    Improvement(f64),
+    /// Within the noise threshold of the baseline. Holds the change in percent.
+    NoChange(f64),
+    /// The baseline has no result for this day, stage and input.
+    Missing,
+    /// The baseline median is zero, so there is no relative change to compute.
+    Incomparable,
+}
+
+impl Comparison {
+    /// Compares the median of `new` against the median of `old`. Changes of at most
+    /// `noise_threshold` percent in either direction are considered noise.
+    pub fn new(old: Option<&Stats>, new: &Stats, noise_threshold: f64) -> Self {
+        let old = match old {
+            Some(old) => old,
+            None => return Comparison::Missing,
+        };
+        // A timer too coarse for a trivial solve can record a median of zero.
+        if old.median <= 0.0 {
+            return Comparison::Incomparable;
+        }
+        let change = (new.median - old.median) / old.median * 100.0;
+        if change > noise_threshold {
+            Comparison::Regression(change)
+        } else if change < -noise_threshold {
+            Comparison::Improvement(change)
+        } else {
+            Comparison::NoChange(change)
+        }
+    }
+
+    /// The change in percent compared to the baseline, if there was anything to compare with.
+    pub fn change(&self) -> Option<f64> {
+        match *self {
+            Comparison::Regression(change) |
+            Comparison::Improvement(change) |
+            Comparison::NoChange(change) => Some(change),
+            Comparison::Missing | Comparison::Incomparable => None,
+        }
+    }
+
+    pub fn is_regression(&self) -> bool {
+        matches!(*self, Comparison::Regression(_))
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Comparison::Regression(change) => write!(f, "{:+.2}% REGRESSION", change),
+            Comparison::Improvement(change) => write!(f, "{:+.2}% improvement", change),
+            Comparison::NoChange(change) => write!(f, "{:+.2}% no change", change),
+            Comparison::Missing => write!(f, "not in baseline"),
+            Comparison::Incomparable => write!(f, "no comparison, baseline median is 0"),
+        }
+    }
+}
+
+/// Hashes the problem input so benchmarks are only compared when run on the same input.
+pub fn input_hash(input: &str) -> u64 {
+    fnv1a(input)
+}
+
+/// Hashes the solver parameters, defaults included, so benchmarks are only compared when run
+/// with the same parameters.
+pub fn params_hash(params: &Params) -> u64 {
+    fnv1a(&params.to_string())
+}
+
+/// 64 bit FNV-1a, used since it is stable between builds and Rust versions.
+fn fnv1a(data: &str) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for byte in data.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use base::Part;
+    use bench::{Stage, Stats};
+    use super::{input_hash, Baseline, Comparison};
+
+    fn stats(median: f64) -> Stats {
+        Stats {
+            median,
+            mean: median + 0.5,
+            std_dev: 1.25,
+            min: median - 2.0,
+            max: median + 3.0,
+            samples: 48,
+            outliers: 2,
+            iterations_per_sample: 100,
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut baseline = Baseline::default();
+        baseline.insert(1, Stage::Parse, input_hash("R2, L3"), 1, stats(1000.0));
+        baseline.insert(1, Stage::Solve(Part::One), input_hash("R2, L3"), 1, stats(12.0));
+        baseline.insert(1, Stage::Solve(Part::One), input_hash("R2, L3"), 2, stats(10.0));
+        baseline.insert(2, Stage::Solve(Part::Two), input_hash("UDLR"), 3, stats(123456.5));
+        let parsed = Baseline::parse(&baseline.to_string()).unwrap();
+        assert_eq!(baseline, parsed);
+    }
+
+    #[test]
+    fn parse_wrong_field_count() {
+        assert!(Baseline::parse("1 1 00ff 1 2 3").is_err());
+        // Without the params hash of older baselines.
+        assert!(Baseline::parse("1 parse 00ff 1 2 3 4 5 6 7 8").is_err());
+    }
+
+    #[test]
+    fn params_in_key() {
+        let mut baseline = Baseline::default();
+        baseline.insert(2, Stage::Parse, input_hash("UDLR"), 7, stats(100.0));
+        assert!(baseline.get(2, Stage::Parse, input_hash("UDLR"), 7).is_some());
+        assert!(baseline.get(2, Stage::Parse, input_hash("UDLR"), 8).is_none());
+    }
+
+    #[test]
+    fn input_hash_known_values() {
+        assert_eq!(0xcbf29ce484222325, input_hash(""));
+        assert_eq!(0xaf63dc4c8601ec8c, input_hash("a"));
+    }
+
+    #[test]
+    fn compare_regression() {
+        let comparison = Comparison::new(Some(&stats(100.0)), &stats(120.0), 5.0);
+        assert_eq!(Comparison::Regression(20.0), comparison);
+        assert!(comparison.is_regression());
+    }
+
+    #[test]
+    fn compare_improvement() {
+        let comparison = Comparison::new(Some(&stats(100.0)), &stats(50.0), 5.0);
+        assert_eq!(Comparison::Improvement(-50.0), comparison);
+    }
+
+    #[test]
+    fn compare_within_noise() {
+        let comparison = Comparison::new(Some(&stats(100.0)), &stats(104.0), 5.0);
+        assert_eq!(Comparison::NoChange(4.0), comparison);
+        assert!(!comparison.is_regression());
+    }
+
+    #[test]
+    fn compare_missing() {
+        assert_eq!(Comparison::Missing, Comparison::new(None, &stats(1.0), 5.0));
+    }
+
+    #[test]
+    fn compare_zero_baseline() {
+        let comparison = Comparison::new(Some(&stats(0.0)), &stats(20.0), 5.0);
+        assert_eq!(Comparison::Incomparable, comparison);
+        assert_eq!(None, comparison.change());
+        assert!(!comparison.is_regression());
+        let comparison = Comparison::new(Some(&stats(0.0)), &stats(0.0), 5.0);
+        assert_eq!(Comparison::Incomparable, comparison);
+    }
+}
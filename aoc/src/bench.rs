@@ -0,0 +1,227 @@
+use base::Part;
+
+use std::fmt;
+use std::hint::black_box;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// The stage of solving a problem that a benchmark measures.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Stage {
+    /// Parsing the input.
+    Parse,
+    /// Solving the given part with already parsed input.
+    Solve(Part),
+}
+
+impl FromStr for Stage {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "parse" => Ok(Stage::Parse),
+            _ => Part::from_str(s).map(Stage::Solve).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Stage::Parse => write!(f, "parse"),
+            Stage::Solve(part) => write!(f, "{}", part),
+        }
+    }
+}
+
+/// Settings controlling how long and how many times a benchmark runs.
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    /// How long to run the function before starting to measure.
+    pub warmup_time: Duration,
+    /// Roughly how long to spend collecting samples.
+    pub measurement_time: Duration,
+    /// How many samples to collect. Each sample runs the function one or more times.
+    pub samples: usize,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        BenchConfig {
+            warmup_time: Duration::from_millis(500),
+            measurement_time: Duration::from_secs(2),
+            samples: 50,
+        }
+    }
+}
+
+/// Statistics over the time per iteration of a benchmarked function. All times are in
+/// nanoseconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub median: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Number of samples the statistics are computed over, after outlier rejection.
+    pub samples: usize,
+    /// Number of samples that were rejected as outliers.
+    pub outliers: usize,
+    /// How many times the function ran in each sample.
+    pub iterations_per_sample: u64,
+}
+
+impl Stats {
+    /// Computes the statistics over the given samples, given in nanoseconds per iteration.
+    /// Samples outside of 1.5 times the interquartile range from the quartiles (Tukey's fences)
+    /// are rejected as outliers before computing anything but the outlier count.
+    pub fn from_samples(samples: &[f64], iterations_per_sample: u64) -> Self {
+        assert!(!samples.is_empty(), "Can't compute statistics without samples");
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let q1 = percentile(&sorted, 25.0);
+        let q3 = percentile(&sorted, 75.0);
+        let iqr = q3 - q1;
+        let (low_fence, high_fence) = (q1 - 1.5 * iqr, q3 + 1.5 * iqr);
+        let kept: Vec<f64> =
+            sorted.iter().cloned().filter(|&s| s >= low_fence && s <= high_fence).collect();
+
+        let mean = kept.iter().sum::<f64>() / kept.len() as f64;
+        let variance = if kept.len() > 1 {
+            kept.iter().map(|s| (s - mean) * (s - mean)).sum::<f64>() / (kept.len() - 1) as f64
+        } else {
+            0.0
+        };
+        Stats {
+            median: percentile(&kept, 50.0),
+            mean,
+            std_dev: variance.sqrt(),
+            min: kept[0],
+            max: kept[kept.len() - 1],
+            samples: kept.len(),
+            outliers: sorted.len() - kept.len(),
+            iterations_per_sample,
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+               "{} ns/iter (mean {} +/- {}, min {}, max {}, {} samples of {} iterations, {} \
+                outliers)",
+               format_ns(self.median),
+               format_ns(self.mean),
+               format_ns(self.std_dev),
+               format_ns(self.min),
+               format_ns(self.max),
+               self.samples,
+               self.iterations_per_sample,
+               self.outliers)
+    }
+}
+
+/// Benchmarks the given function. It first runs for the warmup time to estimate how long one
+/// iteration takes, then picks the number of iterations per sample so that all samples fit in
+/// roughly the measurement time.
+pub fn benchmark<F, T>(config: &BenchConfig, mut f: F) -> Stats
+    where F: FnMut() -> T
+{
+    let warmup_timer = Instant::now();
+    let mut warmup_iterations = 0u64;
+    while warmup_iterations == 0 || warmup_timer.elapsed() < config.warmup_time {
+        black_box(f());
+        warmup_iterations += 1;
+    }
+    let ns_per_iteration = duration_ns(warmup_timer.elapsed()) / warmup_iterations as f64;
+
+    let samples = config.samples.max(1);
+    let ns_per_sample = duration_ns(config.measurement_time) / samples as f64;
+    let iterations_per_sample = (ns_per_sample / ns_per_iteration.max(1.0)).max(1.0) as u64;
+
+    let mut sample_times = Vec::with_capacity(samples);
+    for _ in 0..samples {
+        let sample_timer = Instant::now();
+        for _ in 0..iterations_per_sample {
+            black_box(f());
+        }
+        sample_times.push(duration_ns(sample_timer.elapsed()) / iterations_per_sample as f64);
+    }
+    Stats::from_samples(&sample_times, iterations_per_sample)
+}
+
+/// Formats a number of nanoseconds rounded to an integer and with thousands separators.
+pub fn format_ns(ns: f64) -> String {
+    let digits = format!("{:.0}", ns);
+    let mut result = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            result.push(',');
+        }
+        result.push(c);
+    }
+    result
+}
+
+fn duration_ns(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1e9 + duration.subsec_nanos() as f64
+}
+
+/// Linear interpolation between closest ranks of an already sorted slice.
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let rank = percent / 100.0 * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use base::Part;
+    use std::str::FromStr;
+    use super::{format_ns, Stage, Stats};
+
+    #[test]
+    fn stage_roundtrip() {
+        for stage in &[Stage::Parse, Stage::Solve(Part::One), Stage::Solve(Part::Two)] {
+            assert_eq!(*stage, Stage::from_str(&stage.to_string()).unwrap());
+        }
+    }
+
+    #[test]
+    fn stats_without_outliers() {
+        let stats = Stats::from_samples(&[4.0, 1.0, 3.0, 2.0, 5.0], 10);
+        assert_eq!(3.0, stats.median);
+        assert_eq!(3.0, stats.mean);
+        assert_eq!(1.0, stats.min);
+        assert_eq!(5.0, stats.max);
+        assert!((stats.std_dev - 2.5f64.sqrt()).abs() < 1e-9);
+        assert_eq!(5, stats.samples);
+        assert_eq!(0, stats.outliers);
+        assert_eq!(10, stats.iterations_per_sample);
+    }
+
+    #[test]
+    fn stats_rejects_outliers() {
+        let stats = Stats::from_samples(&[10.0, 11.0, 10.0, 12.0, 11.0, 1000.0], 1);
+        assert_eq!(1, stats.outliers);
+        assert_eq!(5, stats.samples);
+        assert_eq!(12.0, stats.max);
+    }
+
+    #[test]
+    fn stats_single_sample() {
+        let stats = Stats::from_samples(&[7.0], 1);
+        assert_eq!(7.0, stats.median);
+        assert_eq!(0.0, stats.std_dev);
+    }
+
+    #[test]
+    fn format_ns_separators() {
+        assert_eq!("0", format_ns(0.2));
+        assert_eq!("999", format_ns(999.0));
+        assert_eq!("1,000", format_ns(1000.0));
+        assert_eq!("21,765", format_ns(21765.4));
+        assert_eq!("1,234,567", format_ns(1234567.0));
+    }
+}
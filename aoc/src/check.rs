@@ -0,0 +1,82 @@
+use base::ParseError;
+
+use std::fmt::Write;
+
+/// Formats a parse error found in `input`, read from `input_path`, for showing to the user. The
+/// location and message are followed by the line with the error, with carets under the
+/// offending text.
+pub fn format_diagnostic(input_path: &str, input: &str, error: &ParseError) -> String {
+    let span = match error.span() {
+        Some(span) => span,
+        None => return format!("{}: {}\n", input_path, error.message()),
+    };
+    let line_start = input[..span.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = input[span.start..].find('\n').map(|i| span.start + i).unwrap_or(input.len());
+    let line = input[line_start..line_end].trim_end_matches('\r');
+
+    // Keep tabs in the indentation so the carets line up with the text above them.
+    let indent: String = input[line_start..span.start]
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let end = span.end.min(line_start + line.len()).max(span.start);
+    let carets = input[span.start..end].chars().count().max(1);
+
+    let line_number = span.line.to_string();
+    let gutter = " ".repeat(line_number.len());
+    let mut diagnostic = String::new();
+    let _ = writeln!(diagnostic,
+                     "{}:{}:{}: {}",
+                     input_path,
+                     span.line,
+                     span.column,
+                     error.message());
+    let _ = writeln!(diagnostic, "{} |", gutter);
+    let _ = writeln!(diagnostic, "{} | {}", line_number, line);
+    let _ = writeln!(diagnostic, "{} | {}{}", gutter, indent, "^".repeat(carets));
+    diagnostic
+}
+
+#[cfg(test)]
+mod tests {
+    use base::{ParseError, Span};
+    use super::format_diagnostic;
+
+    #[test]
+    fn carets_under_span() {
+        let input = "R2, L3\nR4, X11, L2\n";
+        let error = ParseError::new("Invalid direction: X").with_span(Span::new(input, 11, 14));
+        assert_eq!("inputs/1:2:5: Invalid direction: X\n  |\n2 | R4, X11, L2\n  |     ^^^\n",
+                   format_diagnostic("inputs/1", input, &error));
+    }
+
+    #[test]
+    fn empty_span_at_end_of_input() {
+        let input = "R2, L";
+        let error = ParseError::new("Invalid distance: ").with_span(Span::new(input, 5, 5));
+        assert_eq!("in:1:6: Invalid distance: \n  |\n1 | R2, L\n  |      ^\n",
+                   format_diagnostic("in", input, &error));
+    }
+
+    #[test]
+    fn span_past_end_of_line_is_cut() {
+        let input = "1 2\r\n3 4\n";
+        let error = ParseError::new("Bad").with_span(Span::new(input, 2, 9));
+        assert_eq!("in:1:3: Bad\n  |\n1 | 1 2\n  |   ^\n",
+                   format_diagnostic("in", input, &error));
+    }
+
+    #[test]
+    fn keeps_tabs_in_indentation() {
+        let input = "\t1\tx";
+        let error = ParseError::new("Invalid number: x").with_span(Span::new(input, 3, 4));
+        assert_eq!("in:1:4: Invalid number: x\n  |\n1 | \t1\tx\n  | \t \t^\n",
+                   format_diagnostic("in", input, &error));
+    }
+
+    #[test]
+    fn without_span() {
+        let error = ParseError::new("Empty input");
+        assert_eq!("in: Empty input\n", format_diagnostic("in", "", &error));
+    }
+}
@@ -0,0 +1,411 @@
+use isolate;
+use output::Format;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The name of the project config file.
+pub static CONFIG_FILE_NAME: &str = "aoc.toml";
+
+/// Settings read from an `aoc.toml` project config file. Every setting is optional and is
+/// overridden by the matching command line flag.
+///
+/// The file is a small subset of TOML: `key = value` lines, `[table]` headers and `#`
+/// comments. Values are strings, integers, floats or booleans. Relative paths are relative to
+/// the directory of the config file.
+///
+/// ```toml
+/// inputs_dir = "inputs"
+/// answers = "answers"
+/// output = "plain"
+/// timeout = "10s"
+///
+/// [bench]
+/// warmup_ms = 500
+/// measurement_ms = 2000
+/// samples = 50
+/// noise_threshold = 5.0
+/// baselines_dir = "baselines"
+///
+/// [params.2]
+/// keypad = "crazy"
+/// ```
+#[derive(Debug, Default, PartialEq)]
+pub struct Config {
+    pub inputs_dir: Option<String>,
+    pub answers: Option<String>,
+    pub output: Option<Format>,
+    pub timeout: Option<Duration>,
+    pub warmup_ms: Option<u64>,
+    pub measurement_ms: Option<u64>,
+    pub samples: Option<usize>,
+    pub noise_threshold: Option<f64>,
+    pub baselines_dir: Option<String>,
+    /// Solver parameters per day, in the order they are written.
+    pub params: BTreeMap<u8, Vec<(String, String)>>,
+}
+
+/// Checks the parameters given for a day, returning why they are invalid.
+pub type ParamCheck = dyn Fn(u8, &[(String, String)]) -> Result<(), String>;
+
+impl Config {
+    /// Looks for `aoc.toml` in the current directory and then in each parent directory, and
+    /// loads the first one found. Returns an empty config if there is none.
+    pub fn discover(check_params: &ParamCheck) -> Result<Self, String> {
+        let current_dir = env::current_dir()
+            .map_err(|e| format!("Unable to get the current directory: {}", e))?;
+        for dir in current_dir.ancestors() {
+            let path = dir.join(CONFIG_FILE_NAME);
+            if path.is_file() {
+                return Self::load(&path, check_params);
+            }
+        }
+        Ok(Self::default())
+    }
+
+    /// Loads the config file at the given path. Unlike `discover` it is an error if the file
+    /// does not exist.
+    pub fn load(path: &Path, check_params: &ParamCheck) -> Result<Self, String> {
+        let mut data = String::new();
+        File::open(path)
+            .and_then(|mut f| f.read_to_string(&mut data))
+            .map_err(|e| format!("Unable to read config {}: {}", path.display(), e))?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        Self::parse(&data, base_dir, check_params)
+            .map_err(|e| format!("Invalid config {}: {}", path.display(), e))
+    }
+
+    /// Parses the config. Relative paths in it are taken to be relative to `base_dir`. The
+    /// parameters of a day are checked with `check_params` every time one is added, so an
+    /// invalid one is reported with its line.
+    pub fn parse(data: &str, base_dir: &Path, check_params: &ParamCheck) -> Result<Self, String> {
+        let mut config = Config::default();
+        let mut table = String::new();
+        for (i, line) in data.lines().enumerate() {
+            let line_number = i + 1;
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') {
+                if !line.ends_with(']') {
+                    return Err(format!("Unterminated table header on line {}", line_number));
+                }
+                table = line[1..line.len() - 1].trim().to_owned();
+                validate_table(&table).map_err(|e| format!("{} on line {}", e, line_number))?;
+                continue;
+            }
+            let (key, value) = match line.find('=') {
+                Some(i) => (line[..i].trim(), line[i + 1..].trim()),
+                None => return Err(format!("Expected key = value on line {}", line_number)),
+            };
+            let full_key = if table.is_empty() {
+                key.to_owned()
+            } else {
+                format!("{}.{}", table, key)
+            };
+            let value = Value::parse(value)
+                .map_err(|e| format!("{} for `{}` on line {}", e, full_key, line_number))?;
+            config.set(&table, key, &full_key, value, base_dir, check_params)
+                .map_err(|e| format!("{} on line {}", e, line_number))?;
+        }
+        Ok(config)
+    }
+
+    fn set(&mut self,
+           table: &str,
+           key: &str,
+           full_key: &str,
+           value: Value,
+           base_dir: &Path,
+           check_params: &ParamCheck)
+           -> Result<(), String> {
+        if let Some(day) = table.strip_prefix("params.") {
+            let day = u8::from_str(day).unwrap();
+            let params = self.params.entry(day).or_default();
+            params.push((key.to_owned(), value.to_string()));
+            return check_params(day, params).map_err(|e| format!("{} for `{}`", e, full_key));
+        }
+        match full_key {
+            "inputs_dir" => self.inputs_dir = Some(resolve(base_dir, value.string(full_key)?)),
+            "answers" => self.answers = Some(resolve(base_dir, value.string(full_key)?)),
+            "output" => {
+                let format = value.string(full_key)?;
+                self.output = Some(Format::from_str(&format)
+                    .map_err(|_| invalid(full_key, "plain, json or csv"))?)
+            }
+            "timeout" => {
+                let timeout = value.string(full_key)?;
+                self.timeout = Some(isolate::parse_duration(&timeout)
+                    .map_err(|_| invalid(full_key, "a duration such as 500ms or 2s"))?)
+            }
+            "bench.warmup_ms" => self.warmup_ms = Some(value.unsigned(full_key)?),
+            "bench.measurement_ms" => self.measurement_ms = Some(value.unsigned(full_key)?),
+            "bench.samples" => {
+                match value.unsigned(full_key)? {
+                    0 => return Err(invalid(full_key, "1 or more")),
+                    samples => self.samples = Some(samples as usize),
+                }
+            }
+            "bench.noise_threshold" => self.noise_threshold = Some(value.float(full_key)?),
+            "bench.baselines_dir" => {
+                self.baselines_dir = Some(resolve(base_dir, value.string(full_key)?))
+            }
+            _ => return Err(format!("Unknown key `{}`", full_key)),
+        }
+        Ok(())
+    }
+}
+
+fn invalid(key: &str, expected: &str) -> String {
+    format!("Invalid value for `{}`, expected {}", key, expected)
+}
+
+/// Checks that the table is one the config has.
+fn validate_table(table: &str) -> Result<(), String> {
+    if table == "bench" {
+        return Ok(());
+    }
+    match table.strip_prefix("params.") {
+        Some(day) => {
+            match u8::from_str(day) {
+                Ok(day) if (1..=25).contains(&day) => Ok(()),
+                _ => Err(format!("Invalid day in table `[{}]`, must be 1-25", table)),
+            }
+        }
+        None => Err(format!("Unknown table `[{}]`", table)),
+    }
+}
+
+/// Makes a relative path in the config relative to the config file's directory. Paths are kept
+/// relative to the current directory when possible, so they match the paths in the answers
+/// file.
+fn resolve(base_dir: &Path, path: String) -> String {
+    let path = base_dir.join(path);
+    let path = match env::current_dir() {
+        Ok(ref current_dir) => path.strip_prefix(current_dir).map(PathBuf::from).unwrap_or(path),
+        Err(_) => path,
+    };
+    path.to_string_lossy().into_owned()
+}
+
+/// Removes a `#` comment from the end of the line, unless the `#` is inside a string.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '#') => return &line[..i],
+            _ => (),
+        }
+        escaped = false;
+    }
+    line
+}
+
+/// A value in the config file.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl Value {
+    fn parse(s: &str) -> Result<Self, String> {
+        if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+            unescape(&s[1..s.len() - 1]).map(Value::Str)
+        } else if s.len() >= 2 && s.starts_with('\'') && s.ends_with('\'') {
+            Ok(Value::Str(s[1..s.len() - 1].to_owned()))
+        } else if s == "true" || s == "false" {
+            Ok(Value::Bool(s == "true"))
+        } else if let Ok(n) = i64::from_str(&s.replace('_', "")) {
+            Ok(Value::Int(n))
+        } else if let Ok(n) = f64::from_str(&s.replace('_', "")) {
+            Ok(Value::Float(n))
+        } else {
+            Err(format!("Invalid value {}", s))
+        }
+    }
+
+    fn string(self, key: &str) -> Result<String, String> {
+        match self {
+            Value::Str(s) => Ok(s),
+            _ => Err(invalid(key, "a string")),
+        }
+    }
+
+    fn unsigned(self, key: &str) -> Result<u64, String> {
+        match self {
+            Value::Int(n) if n >= 0 => Ok(n as u64),
+            _ => Err(invalid(key, "a non-negative integer")),
+        }
+    }
+
+    fn float(self, key: &str) -> Result<f64, String> {
+        match self {
+            Value::Int(n) => Ok(n as f64),
+            Value::Float(n) => Ok(n),
+            _ => Err(invalid(key, "a number")),
+        }
+    }
+}
+
+impl ::std::fmt::Display for Value {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            Value::Str(ref s) => write!(f, "{}", s),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Float(n) => write!(f, "{}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+/// Handles the escape sequences of TOML basic strings, except for unicode escapes.
+fn unescape(s: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('"') => result.push('"'),
+            Some('\\') => result.push('\\'),
+            other => {
+                return Err(format!("Invalid escape sequence \\{}",
+                                   other.map(|c| c.to_string()).unwrap_or_default()))
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use output::Format;
+    use std::path::Path;
+    use std::time::Duration;
+    use super::Config;
+
+    fn parse(data: &str) -> Result<Config, String> {
+        Config::parse(data, Path::new(""), &|_, _| Ok(()))
+    }
+
+    #[test]
+    fn full_config() {
+        let config = parse("# Project settings\n\
+                            inputs_dir = \"my inputs\" # comment\n\
+                            answers = 'answers.txt'\n\
+                            output = \"json\"\n\
+                            timeout = \"1.5s\"\n\
+                            \n\
+                            [bench]\n\
+                            warmup_ms = 100\n\
+                            measurement_ms = 1_000\n\
+                            samples = 20\n\
+                            noise_threshold = 2.5\n\
+                            baselines_dir = \"b#1\"\n\
+                            \n\
+                            [params.1]\n\
+                            start = \"3,4\"\n\
+                            heading = \"E\"\n\
+                            [params.2]\n\
+                            keypad = \"crazy\"\n")
+            .unwrap();
+        assert_eq!(Some("my inputs".to_owned()), config.inputs_dir);
+        assert_eq!(Some("answers.txt".to_owned()), config.answers);
+        assert_eq!(Some(Format::Json), config.output);
+        assert_eq!(Some(Duration::from_millis(1500)), config.timeout);
+        assert_eq!(Some(100), config.warmup_ms);
+        assert_eq!(Some(1000), config.measurement_ms);
+        assert_eq!(Some(20), config.samples);
+        assert_eq!(Some(2.5), config.noise_threshold);
+        assert_eq!(Some("b#1".to_owned()), config.baselines_dir);
+        assert_eq!(vec![("start".to_owned(), "3,4".to_owned()),
+                        ("heading".to_owned(), "E".to_owned())],
+                   config.params[&1]);
+        assert_eq!(vec![("keypad".to_owned(), "crazy".to_owned())], config.params[&2]);
+    }
+
+    #[test]
+    fn empty_config() {
+        assert_eq!(Config::default(), parse("\n# nothing here\n").unwrap());
+    }
+
+    #[test]
+    fn relative_paths() {
+        let config = Config::parse("inputs_dir = \"inputs\"", Path::new("/project"), &|_, _| Ok(()))
+            .unwrap();
+        assert_eq!(Some("/project/inputs".to_owned()), config.inputs_dir);
+    }
+
+    #[test]
+    fn unknown_key() {
+        assert_eq!(Err("Unknown key `bench.warmup` on line 2".to_owned()),
+                   parse("[bench]\nwarmup = 5"));
+        assert_eq!(Err("Unknown key `inputs` on line 1".to_owned()), parse("inputs = \"x\""));
+    }
+
+    #[test]
+    fn unknown_table() {
+        assert_eq!(Err("Unknown table `[benchmark]` on line 1".to_owned()),
+                   parse("[benchmark]"));
+        assert!(parse("[params.26]").is_err());
+        assert!(parse("[params.x]").is_err());
+    }
+
+    #[test]
+    fn invalid_values() {
+        assert_eq!(Err("Invalid value for `output`, expected plain, json or csv on line 1"
+                       .to_owned()),
+                   parse("output = \"xml\""));
+        assert_eq!(Err("Invalid value for `bench.samples`, expected a non-negative integer on \
+                        line 2"
+                       .to_owned()),
+                   parse("[bench]\nsamples = \"many\""));
+        assert!(parse("[bench]\nsamples = 0").is_err());
+        assert!(parse("answers = 5").is_err());
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert_eq!(Err("Expected key = value on line 1".to_owned()), parse("inputs_dir"));
+        assert_eq!(Err("Invalid value \"unterminated for `answers` on line 1".to_owned()),
+                   parse("answers = \"unterminated"));
+        assert!(parse("[bench").is_err());
+    }
+
+    #[test]
+    fn param_values_as_strings() {
+        let config = parse("[params.5]\nn = 10\nfast = true").unwrap();
+        assert_eq!(vec![("n".to_owned(), "10".to_owned()), ("fast".to_owned(), "true".to_owned())],
+                   config.params[&5]);
+    }
+
+    #[test]
+    fn invalid_params_have_line() {
+        let check = |day, params: &[(String, String)]| match params.last() {
+            Some((_, value)) if value == "10" => Err(format!("Too large for day {}", day)),
+            _ => Ok(()),
+        };
+        assert_eq!(Err("Too large for day 5 for `params.5.n` on line 3".to_owned()),
+                   Config::parse("[params.5]\nfast = true\nn = 10", Path::new(""), &check));
+    }
+}
@@ -0,0 +1,90 @@
+use std::any::Any;
+use std::panic;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+/// The name of the threads solvers run on.
+pub static SOLVER_THREAD: &str = "solver";
+
+/// Stops panics on solver threads from being printed. They are reported as the result of the
+/// solve instead. Panics on other threads are printed as usual.
+pub fn silence_solver_panics() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().name() != Some(SOLVER_THREAD) {
+            default_hook(info);
+        }
+    }));
+}
+
+/// Returns the message of a caught panic.
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_owned()
+    }
+}
+
+/// Parses a duration such as `500ms`, `2s` or `1m`. A number without a unit is in seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number = f64::from_str(number)
+        .map_err(|_| format!("Invalid duration {:?}, expected for example 500ms or 2s", s))?;
+    let seconds = match unit.trim() {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" | "min" => number * 60.0,
+        unit => {
+            return Err(format!("Invalid unit {:?} in duration {:?}, expected ms, s or m", unit, s))
+        }
+    };
+    if seconds <= 0.0 {
+        return Err(format!("Duration {:?} must be longer than zero", s));
+    }
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("Duration {:?} is too long", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+    use std::time::Duration;
+    use super::{panic_message, parse_duration};
+
+    #[test]
+    fn durations() {
+        assert_eq!(Ok(Duration::from_millis(500)), parse_duration("500ms"));
+        assert_eq!(Ok(Duration::from_millis(2500)), parse_duration("2.5s"));
+        assert_eq!(Ok(Duration::from_secs(3)), parse_duration("3"));
+        assert_eq!(Ok(Duration::from_secs(120)), parse_duration("2m"));
+    }
+
+    #[test]
+    fn invalid_durations() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("ms").is_err());
+        assert!(parse_duration("5h").is_err());
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("-1s").is_err());
+    }
+
+    #[test]
+    fn oversized_duration() {
+        assert_eq!(Err("Duration \"99999999999999999999999\" is too long".to_owned()),
+                   parse_duration("99999999999999999999999"));
+        assert!(parse_duration("99999999999999999999999m").is_err());
+    }
+
+    #[test]
+    fn panic_messages() {
+        let payload = panic::catch_unwind(|| panic!("static message")).unwrap_err();
+        assert_eq!("static message", panic_message(&*payload));
+        let payload = panic::catch_unwind(|| panic!("formatted {}", 5)).unwrap_err();
+        assert_eq!("formatted 5", panic_message(&*payload));
+    }
+}
@@ -1,29 +1,59 @@
-#![cfg_attr(feature = "bench", feature(test))]
-
 #[macro_use]
 extern crate clap;
 
-#[cfg(feature = "bench")]
-extern crate test;
-
 extern crate base;
-extern crate day1;
-extern crate day2;
-extern crate day3;
+#[cfg(target_os = "linux")]
+extern crate libc;
+
+// Declares the day crates and `register_solvers`, generated by the build script.
+include!(concat!(env!("OUT_DIR"), "/solvers.rs"));
+
+mod allocations;
+mod answers;
+mod baseline;
+mod bench;
+mod check;
+mod config;
+mod isolate;
+mod output;
+mod sandbox;
+mod watch;
 
-use base::{Part, ProblemSolver};
+use allocations::{AllocStats, CountingAllocator};
+use answers::AnswerStore;
+use baseline::{Baseline, Comparison};
+use bench::{BenchConfig, Stage, Stats};
+use config::Config;
+use output::{Format, Record, Value};
+use watch::Watcher;
+use base::{DynProblemSolver, Error, Params, ParseError, Part, Registry, Solution};
 
-use clap::{Arg, App};
+use clap::{App, AppSettings, Arg, SubCommand};
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
 use std::fs::File;
 use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use std::process;
 use std::str::FromStr;
+use std::sync::mpsc;
+use std::thread;
 use std::time::{Instant, Duration};
 
-static APP_NAME: &'static str = "Advent of Code 2016 CLI";
-static APP_VERSION: &'static str = "0.0.0";
-static APP_AUTHOR: &'static str = "Linus Färnstrand <faern@faern.net>";
-static APP_ABOUT: &'static str = "Run Advent of Code solutions";
+static APP_NAME: &str = "Advent of Code 2016 CLI";
+static APP_VERSION: &str = "0.0.0";
+static APP_AUTHOR: &str = "Linus Färnstrand <faern@faern.net>";
+static APP_ABOUT: &str = "Run Advent of Code solutions";
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The input path meaning that the input is read from stdin.
+const STDIN_PATH: &str = "-";
+
+/// The year of the Advent of Code problems the solvers are for.
+const YEAR: u16 = 2016;
 
 /// Macro for printing to stderr. Will simply do nothing if the printing fails for some reason.
 macro_rules! eprintln {
@@ -33,84 +63,1769 @@ macro_rules! eprintln {
     )
 }
 
+/// The subcommands of the CLI.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Command {
+    /// Solve, check or benchmark problems. What the CLI does without a subcommand.
+    Run,
+    /// List the days that have a solver.
+    List,
+    /// Show the status of every day of the calendar.
+    Status,
+}
+
+/// What the user asked the CLI to do, as parsed from the command line.
+struct Options {
+    command: Command,
+    day: Option<u8>,
+    /// The part to solve, or `None` to solve every part the solver supports.
+    part: Option<Part>,
+    /// Where to read the input for the selected day from, `-` for stdin.
+    input_path: Option<String>,
+    inputs_dir: String,
+    answers_path: String,
+    all: bool,
+    check: bool,
+    bench: bool,
+    verify: bool,
+    record: bool,
+    baselines_dir: String,
+    save_baseline: Option<String>,
+    compare_baseline: Option<String>,
+    noise_threshold: f64,
+    bench_config: BenchConfig,
+    format: Format,
+    quiet: bool,
+    /// Whether to count the allocations made while parsing and solving.
+    count_allocations: bool,
+    /// The limits to solve every part in a separate process with, if sandboxed.
+    sandbox: Option<sandbox::Limits>,
+    /// How long to wait for parsing or solving one part before giving up on it.
+    timeout: Option<Duration>,
+    watch: bool,
+    /// Files and directories to watch for changes in addition to the input.
+    watch_paths: Vec<String>,
+    /// Values for the solver parameters of each day, in the order given. Values from the config
+    /// come before the ones given on the command line, so the command line wins.
+    params: BTreeMap<u8, Vec<(String, String)>>,
+}
+
+impl Options {
+    /// The parameter values for the solver of the given day.
+    fn params(&self, day: u8) -> &[(String, String)] {
+        self.params.get(&day).map(|params| &params[..]).unwrap_or(&[])
+    }
+}
+
+/// The default of `--noise-threshold`, in percent.
+const DEFAULT_NOISE_THRESHOLD: f64 = 5.0;
+
+/// The default CPU time limit of sandbox workers.
+const DEFAULT_CPU_LIMIT_SECS: u64 = 60;
+
+/// The default address space limit of sandbox workers, in bytes.
+const DEFAULT_MEMORY_LIMIT: u64 = 2 << 30;
+
+/// Exit code for failures that are not caused by a solver, such as failed verification.
+const EXIT_FAILURE: i32 = 1;
+
+/// Returns the code the program exits with when failing with the given error.
+fn exit_code(error: &Error) -> i32 {
+    match *error {
+        Error::InvalidArgument(_) => 2,
+        Error::Parse(_) => 3,
+        Error::NoSolution(_) => 4,
+        Error::Unsupported(_) => 5,
+        Error::Io(_) => 6,
+        Error::Panicked(_) => 7,
+        Error::TimedOut(_) => 8,
+        Error::Killed(_) => 9,
+        Error::Overflow(_) => 10,
+    }
+}
+
 fn main() {
-    let (day, part, input_path, bench) = parse_arguments().unwrap_or_else(|e| {
+    isolate::silence_solver_panics();
+    if env::args_os().nth(1).is_some_and(|arg| arg == sandbox::WORKER_ARG) {
+        run_sandbox_worker();
+    }
+    let options = parse_arguments().unwrap_or_else(|e| {
         eprintln!("Unable to parse arguments: {}", e);
-        process::exit(1);
-    });
-    let input = read_input(&input_path).unwrap_or_else(|e| {
-        eprintln!("Unable to read input from {}: {}", input_path, e);
-        process::exit(1);
+        process::exit(exit_code(&e));
     });
-    let solver = get_problem_solver(day).unwrap_or_else(|e| {
+    if options.count_allocations {
+        allocations::enable();
+    }
+    match options.command {
+        Command::Run => (),
+        Command::List => {
+            print_solver_list(&options);
+            return;
+        }
+        Command::Status => {
+            let answers = AnswerStore::load(&options.answers_path).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                process::exit(EXIT_FAILURE);
+            });
+            print_status(&options, &day_statuses(&options, &answers));
+            return;
+        }
+    }
+
+    let mut answers = if options.verify || options.record {
+        AnswerStore::load(&options.answers_path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(EXIT_FAILURE);
+        })
+    } else {
+        AnswerStore::default()
+    };
+
+    if options.check {
+        process::exit(run_check(&options));
+    }
+
+    if options.watch {
+        run_watch(&options);
+    }
+
+    if options.bench {
+        let code = run_benchmarks(&options).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(EXIT_FAILURE);
+        });
+        process::exit(code);
+    }
+
+    let results = if options.all {
+        solve_all(&options)
+    } else {
+        let day = options.day.unwrap();
+        // Look up the solver first, so a day without one is reported as such even though its
+        // default input is missing too.
+        let solver = configured_solver(day, options.params(day)).unwrap_or_else(|e| {
+            eprintln!("Error with problem solver: {}", e);
+            process::exit(exit_code(&e));
+        });
+        let input_path = options.input_path.clone().unwrap();
+        let input = read_input(&input_path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(exit_code(&e));
+        });
+        solve_parts(&options, day, &selected_parts(options.part, &*solver), &input_path, &input)
+    };
+    let expected = if options.verify { Some(&answers) } else { None };
+    report_solutions(&options, &results, expected);
+
+    if options.record {
+        for result in &results {
+            if let Ok(ref solution) = result.solution {
+                answers.insert(result.day, result.part, &result.input_path, solution.clone());
+            }
+        }
+        answers.save(&options.answers_path).unwrap_or_else(|e| {
+            eprintln!("Unable to write answers to {}: {}", options.answers_path, e);
+            process::exit(EXIT_FAILURE);
+        });
+    }
+    // Exit after recording, so the parts that were solved are recorded even if another failed.
+    if !options.all {
+        if let Some(e) = results.iter().filter_map(|result| result.solution.as_ref().err()).next() {
+            process::exit(exit_code(e));
+        }
+    }
+    if options.verify && !results.iter().all(|result| verify(result, &answers).is_pass()) {
+        process::exit(EXIT_FAILURE);
+    }
+}
+
+/// How often watch mode checks the watched files for changes.
+const WATCH_INTERVAL_MS: u64 = 300;
+
+/// Environment variable watch mode passes the answers it found in when it restarts itself after
+/// the executable was rebuilt, in the format of the answers file.
+const PREVIOUS_ANSWERS_VAR: &str = "AOC_WATCH_PREVIOUS";
+
+/// Solves the selected day, and then solves it again every time the input or any of the watched
+/// paths change. Every run clears the screen and shows how the answers differ from the previous
+/// run. Errors are shown and then the next change is waited for. If the executable itself is
+/// rebuilt it is restarted, so changes to the solvers are picked up. Never returns.
+fn run_watch(options: &Options) -> ! {
+    let day = options.day.unwrap();
+    if let Err(e) = configured_solver(day, options.params(day)) {
         eprintln!("Error with problem solver: {}", e);
-        process::exit(1);
+        process::exit(exit_code(&e));
+    }
+    let input_path = options.input_path.clone().unwrap();
+    let executable = env::current_exe().ok();
+    let mut paths: Vec<PathBuf> = options.watch_paths.iter().map(PathBuf::from).collect();
+    paths.push(PathBuf::from(&input_path));
+    paths.extend(executable.clone());
+    let mut watcher = Watcher::new(paths, Duration::from_millis(WATCH_INTERVAL_MS));
+    let mut previous = env::var(PREVIOUS_ANSWERS_VAR)
+        .ok()
+        .and_then(|answers| AnswerStore::parse(&answers).ok())
+        .unwrap_or_default();
+
+    loop {
+        print!("{}", watch::CLEAR_SCREEN);
+        println!("Watching {} for changes, press Ctrl-C to stop.\n", input_path);
+        let results = configured_solver(day, options.params(day)).and_then(|solver| {
+            let input = read_input(&input_path)?;
+            let parts = selected_parts(options.part, &*solver);
+            Ok(solve_parts(options, day, &parts, &input_path, &input))
+        });
+        match results {
+            Ok(results) => {
+                print_watch_results(options, &results, &previous);
+                for result in &results {
+                    if let Ok(ref solution) = result.solution {
+                        previous.insert(day, result.part, &input_path, solution.clone());
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+            }
+        }
+
+        let changed = watcher.wait();
+        if let Some(ref executable) = executable {
+            if changed.contains(executable) {
+                let e = restart(executable, &previous);
+                eprintln!("Unable to restart {}: {}", executable.display(), e);
+            }
+        }
+    }
+}
+
+/// Replaces this process with a new run of the executable with the same arguments, passing on
+/// the previous answers. Only returns if that fails.
+#[cfg(unix)]
+fn restart(executable: &Path, previous: &AnswerStore) -> io::Error {
+    use std::os::unix::process::CommandExt;
+    process::Command::new(executable)
+        .args(env::args_os().skip(1))
+        .env(PREVIOUS_ANSWERS_VAR, previous.to_string())
+        .exec()
+}
+
+#[cfg(not(unix))]
+fn restart(_executable: &Path, _previous: &AnswerStore) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, "restarting is only supported on Unix, restart by hand")
+}
+
+/// Prints the results of one run in watch mode, with the change of every answer since the
+/// previous run.
+fn print_watch_results(options: &Options, results: &[RunResult], previous: &AnswerStore) {
+    for result in results {
+        match result.solution {
+            Ok(ref solution) if options.quiet => println!("{}", solution),
+            Ok(ref solution) => {
+                let change = watch::describe_change(previous.get(result.day,
+                                                                 result.part,
+                                                                 &result.input_path),
+                                                    solution);
+                println!("Part {}: {}{}", result.part, solution, change);
+            }
+            Err(ref e) => {
+                eprintln!("Unable to solve problem {}.{}: {}", result.day, result.part, e);
+            }
+        }
+    }
+    if !options.quiet {
+        print_timings(results);
+    }
+}
+
+/// The outcome of solving one part of one day.
+struct RunResult {
+    day: u8,
+    part: Part,
+    input_path: String,
+    solution: Result<Solution, Error>,
+    /// Time spent parsing the input. `None` if the input parsed for another part was reused.
+    parse_time: Option<Duration>,
+    solve_time: Duration,
+    /// The peak resident set size of the process solving the part, if it was sandboxed.
+    peak_rss: Option<u64>,
+    /// The allocations made while parsing, if they were counted and the input was parsed.
+    parse_allocs: Option<AllocStats>,
+    /// The allocations made while solving, if they were counted.
+    solve_allocs: Option<AllocStats>,
+}
+
+/// What the thread solving a day reports back.
+enum SolveEvent {
+    /// The input was parsed, or failed to parse, in the given time with the given allocations.
+    Parsed(Result<(), Error>, Duration, Option<AllocStats>),
+    /// The next part was solved in the given time with the given allocations.
+    Solved(Result<Solution, Error>, Duration, Option<AllocStats>),
+}
+
+/// Solves the given parts as selected by the options, either in sandbox worker processes or on
+/// a thread in this process.
+fn solve_parts(options: &Options,
+               day: u8,
+               parts: &[Part],
+               input_path: &str,
+               input: &str)
+               -> Vec<RunResult> {
+    match options.sandbox {
+        Some(ref limits) => {
+            parts.iter()
+                .map(|&part| solve_in_sandbox(options, limits, day, part, input_path, input))
+                .collect()
+        }
+        None => {
+            solve_on_thread(day, options.params(day), options.timeout, parts, input_path, input)
+        }
+    }
+}
+
+/// Solves one part in a new sandbox worker process with the given resource limits.
+fn solve_in_sandbox(options: &Options,
+                    limits: &sandbox::Limits,
+                    day: u8,
+                    part: Part,
+                    input_path: &str,
+                    input: &str)
+                    -> RunResult {
+    let request = sandbox::Request {
+        day,
+        part,
+        params: options.params(day).to_vec(),
+        timeout: options.timeout,
+        count_allocations: allocations::is_enabled(),
+        input: input.to_owned(),
+    };
+    let timer = Instant::now();
+    let outcome = match env::current_exe() {
+        Ok(executable) => sandbox::run(&executable, &request, limits),
+        Err(e) => {
+            sandbox::Outcome {
+                response: Err(Error::from(e)),
+                peak_rss: None,
+            }
+        }
+    };
+    let peak_rss = outcome.peak_rss;
+    let result = |solution, parse_time, solve_time| {
+        RunResult {
+            day,
+            part,
+            input_path: input_path.to_owned(),
+            solution,
+            parse_time,
+            solve_time,
+            peak_rss,
+            parse_allocs: None,
+            solve_allocs: None,
+        }
+    };
+    match outcome.response {
+        Ok(response) => {
+            RunResult {
+                parse_allocs: response.parse_allocs,
+                solve_allocs: response.solve_allocs,
+                ..result(response.solution, Some(response.parse_time), response.solve_time)
+            }
+        }
+        Err(e) => result(Err(e), None, timer.elapsed()),
+    }
+}
+
+/// Parses the input once and then solves all the given parts with the parsed input. If parsing
+/// fails all parts get the parse error as their solution.
+///
+/// The solver runs on its own thread, so a panic or running past the timeout only fails the
+/// part it happens in. The parts after it are solved on a new thread.
+fn solve_on_thread(day: u8,
+                   params: &[(String, String)],
+                   timeout: Option<Duration>,
+                   parts: &[Part],
+                   input_path: &str,
+                   input: &str)
+                   -> Vec<RunResult> {
+    let mut results = vec![];
+    let mut remaining = parts;
+    while !remaining.is_empty() {
+        let (sender, receiver) = mpsc::channel();
+        let mut worker = Some(spawn_solver(day, params, remaining, input, sender));
+        let mut receive = || -> (Result<SolveEvent, Error>, Duration) {
+            let timer = Instant::now();
+            let event = match timeout {
+                Some(timeout) => {
+                    receiver.recv_timeout(timeout).map_err(|e| match e {
+                        mpsc::RecvTimeoutError::Timeout => Error::TimedOut(timeout),
+                        mpsc::RecvTimeoutError::Disconnected => worker_panic(&mut worker),
+                    })
+                }
+                None => receiver.recv().map_err(|_| worker_panic(&mut worker)),
+            };
+            (event, timer.elapsed())
+        };
+        // The parse time and allocations go in the first result after parsing.
+        let result = |part, solution, parse: Option<(Duration, _)>, solve_time, solve_allocs| {
+            RunResult {
+                day,
+                part,
+                input_path: input_path.to_owned(),
+                solution,
+                parse_time: parse.map(|(time, _)| time),
+                solve_time,
+                peak_rss: None,
+                parse_allocs: parse.and_then(|(_, allocs)| allocs),
+                solve_allocs,
+            }
+        };
+
+        let (parsed, parse) = match receive() {
+            (Ok(SolveEvent::Parsed(parsed, parse_time, parse_allocs)), _) => {
+                (parsed, (parse_time, parse_allocs))
+            }
+            (Err(e), parse_time) => (Err(e), (parse_time, None)),
+            (Ok(SolveEvent::Solved(..)), _) => unreachable!("Solved before parsing"),
+        };
+        let mut parse = Some(parse);
+        if let Err(e) = parsed {
+            for &part in remaining {
+                let solve_time = Duration::new(0, 0);
+                results.push(result(part, Err(e.clone()), parse.take(), solve_time, None));
+            }
+            break;
+        }
+        let mut solved = 0;
+        for &part in remaining {
+            solved += 1;
+            match receive() {
+                (Ok(SolveEvent::Solved(solution, solve_time, solve_allocs)), _) => {
+                    results.push(result(part, solution, parse.take(), solve_time, solve_allocs));
+                }
+                (Err(e), solve_time) => {
+                    results.push(result(part, Err(e), parse.take(), solve_time, None));
+                    break;
+                }
+                (Ok(SolveEvent::Parsed(..)), _) => unreachable!("Parsed twice"),
+            }
+        }
+        remaining = &remaining[solved..];
+    }
+    results
+}
+
+/// Starts a thread that parses the input and then solves the given parts with a new solver for
+/// the day, sending the outcome of every step over `sender`.
+fn spawn_solver(day: u8,
+                params: &[(String, String)],
+                parts: &[Part],
+                input: &str,
+                sender: mpsc::Sender<SolveEvent>)
+                -> thread::JoinHandle<()> {
+    let params = params.to_vec();
+    let parts = parts.to_vec();
+    let input = input.to_owned();
+    thread::Builder::new()
+        .name(isolate::SOLVER_THREAD.to_owned())
+        .spawn(move || {
+            let solver = match configured_solver(day, &params) {
+                Ok(solver) => solver,
+                Err(e) => {
+                    let _ = sender.send(SolveEvent::Parsed(Err(e), Duration::new(0, 0), None));
+                    return;
+                }
+            };
+            let ((parsed_input, parse_time), parse_allocs) =
+                allocations::measure(|| timed(|| solver.parse(&input)));
+            let parsed_input = match parsed_input {
+                Ok(parsed_input) => parsed_input,
+                Err(e) => {
+                    let _ = sender.send(SolveEvent::Parsed(Err(e), parse_time, parse_allocs));
+                    return;
+                }
+            };
+            let _ = sender.send(SolveEvent::Parsed(Ok(()), parse_time, parse_allocs));
+            for part in parts {
+                let ((solution, solve_time), solve_allocs) =
+                    allocations::measure(|| timed(|| solver.solve(part, &parsed_input)));
+                if sender.send(SolveEvent::Solved(solution, solve_time, solve_allocs)).is_err() {
+                    return;
+                }
+            }
+        })
+        .expect("Unable to start solver thread")
+}
+
+/// Runs `f` and returns its result together with how long it took.
+fn timed<F: FnOnce() -> T, T>(f: F) -> (T, Duration) {
+    let timer = Instant::now();
+    let result = f();
+    (result, timer.elapsed())
+}
+
+/// The error for a solver thread that stopped without reporting back, which means it panicked.
+fn worker_panic(worker: &mut Option<thread::JoinHandle<()>>) -> Error {
+    match worker.take().map(|worker| worker.join()) {
+        Some(Err(payload)) => Error::Panicked(isolate::panic_message(&*payload)),
+        _ => Error::Panicked("the solver stopped without a result".to_owned()),
+    }
+}
+
+/// Runs as a sandbox worker. Solves the request read from stdin and writes the response to
+/// stdout. Never returns.
+fn run_sandbox_worker() -> ! {
+    let request = sandbox::Request::read_from(io::stdin().lock()).unwrap_or_else(|e| {
+        eprintln!("Invalid sandbox request: {}", e);
+        process::exit(EXIT_FAILURE);
     });
+    if request.count_allocations {
+        allocations::enable();
+    }
+    let result = solve_on_thread(request.day,
+                                 &request.params,
+                                 request.timeout,
+                                 &[request.part],
+                                 STDIN_PATH,
+                                 &request.input)
+        .remove(0);
+    let response = sandbox::Response {
+        solution: result.solution,
+        parse_time: result.parse_time.unwrap_or_default(),
+        solve_time: result.solve_time,
+        parse_allocs: result.parse_allocs,
+        solve_allocs: result.solve_allocs,
+    };
+    if let Err(e) = response.write_to(io::stdout().lock()) {
+        eprintln!("Unable to write sandbox response: {}", e);
+        process::exit(EXIT_FAILURE);
+    }
+    // Exiting also stops a solver thread that timed out.
+    process::exit(0);
+}
 
-    if bench {
-        benchmark(solver, part, input);
-    } else {
-        solve(solver, day, part, input);
+/// Solves the selected parts of every day that has a solver, reading the input for each day
+/// from `<inputs_dir>/<day>`. A failing day is reported in its result and does not stop the run.
+fn solve_all(options: &Options) -> Vec<RunResult> {
+    let mut results = vec![];
+    for (day, solver) in get_all_problem_solvers(options) {
+        let input_path = default_input_path(&options.inputs_dir, day);
+        let parts = supported_parts(options.part, &*solver);
+        match read_input(&input_path) {
+            Ok(input) => {
+                results.extend(solve_parts(options, day, &parts, &input_path, &input));
+            }
+            Err(e) => {
+                for &part in &parts {
+                    results.push(RunResult {
+                        day,
+                        part,
+                        input_path: input_path.clone(),
+                        solution: Err(e.clone()),
+                        parse_time: None,
+                        solve_time: Duration::new(0, 0),
+                        peak_rss: None,
+                        parse_allocs: None,
+                        solve_allocs: None,
+                    });
+                }
+            }
+        }
     }
+    results
 }
 
-fn solve(solver: Box<ProblemSolver>, day: u8, part: Part, input: String) {
-    let solution_timer = Instant::now();
-    let solution = solver.solve(part, input).unwrap_or_else(|e| {
-        eprintln!("Unable to solve problem {}.{}: {}", day, part, e);
+/// The result of comparing a solution against the expected answer.
+enum Verification {
+    Pass,
+    Mismatch(Solution),
+    Missing,
+    Failed,
+}
+
+impl Verification {
+    fn is_pass(&self) -> bool {
+        match *self {
+            Verification::Pass => true,
+            Verification::Mismatch(_) | Verification::Missing | Verification::Failed => false,
+        }
+    }
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Verification::Pass => write!(f, "ok"),
+            Verification::Mismatch(ref expected) => {
+                write!(f, "MISMATCH, expected {}", single_line(expected))
+            }
+            Verification::Missing => write!(f, "MISSING, no recorded answer"),
+            Verification::Failed => write!(f, "FAILED"),
+        }
+    }
+}
+
+/// Compares the solution against the recorded answer. A part without a recorded answer fails.
+fn verify(result: &RunResult, answers: &AnswerStore) -> Verification {
+    let expected = answers.get(result.day, result.part, &result.input_path);
+    match (result.solution.as_ref(), expected) {
+        (Err(_), _) => Verification::Failed,
+        (Ok(_), None) => Verification::Missing,
+        (Ok(solution), Some(expected)) if solution == expected => Verification::Pass,
+        (Ok(_), Some(expected)) => Verification::Mismatch(expected.clone()),
+    }
+}
+
+/// Prints the results in the format selected by the options. If `expected` is given the results
+/// are also verified against it.
+fn report_solutions(options: &Options, results: &[RunResult], expected: Option<&AnswerStore>) {
+    match options.format {
+        Format::Plain if options.quiet => {
+            for result in results {
+                match result.solution {
+                    Ok(ref solution) => println!("{}", solution),
+                    Err(ref e) => {
+                        eprintln!("Unable to solve problem {}.{}: {}", result.day, result.part, e);
+                    }
+                }
+            }
+        }
+        Format::Plain if options.all => print_summary(results, expected),
+        Format::Plain if results.len() == 1 => {
+            let result = &results[0];
+            match result.solution {
+                Ok(ref solution) => {
+                    println!("Solution: {}\nTime to parse: {}{}\nTime to solve: {}{}",
+                             solution,
+                             format_optional_duration(result.parse_time),
+                             format_allocs(result.parse_allocs),
+                             format_duration(&result.solve_time),
+                             format_allocs(result.solve_allocs))
+                }
+                Err(ref e) => {
+                    eprintln!("Unable to solve problem {}.{}: {}", result.day, result.part, e);
+                }
+            }
+            if let Some(peak_rss) = result.peak_rss {
+                println!("Peak RSS: {}", sandbox::format_size(peak_rss));
+            }
+            if let Some(expected) = expected {
+                println!("Verification: {}", verify(result, expected));
+            }
+        }
+        Format::Plain => print_parts(results, expected),
+        format => {
+            let records: Vec<Record> = results.iter()
+                .map(|result| {
+                    let mut record = vec![("day", Value::UInt(result.day as u64)),
+                                          ("part", Value::UInt(part_number(result.part))),
+                                          ("input", Value::from(result.input_path.as_str())),
+                                          ("answer", Value::from(result.solution.clone().ok())),
+                                          ("error",
+                                           Value::from(result.solution
+                                               .as_ref()
+                                               .err()
+                                               .map(|e| e.to_string()))),
+                                          ("parse_time_ns",
+                                           Value::from(result.parse_time
+                                               .map(|time| duration_ns(&time)))),
+                                          ("solve_time_ns",
+                                           Value::from(duration_ns(&result.solve_time)))];
+                    if options.count_allocations {
+                        record.extend(alloc_fields(["parse_allocations",
+                                                    "parse_allocated_bytes",
+                                                    "parse_peak_bytes"],
+                                                   result.parse_allocs));
+                        record.extend(alloc_fields(["solve_allocations",
+                                                    "solve_allocated_bytes",
+                                                    "solve_peak_bytes"],
+                                                   result.solve_allocs));
+                    }
+                    if options.sandbox.is_some() {
+                        let kill_reason = match result.solution {
+                            Err(Error::Killed(ref reason)) => Some(reason.as_str()),
+                            _ => None,
+                        };
+                        record.push(("peak_rss_bytes", Value::from(result.peak_rss)));
+                        record.push(("kill_reason", Value::from(kill_reason)));
+                    }
+                    if let Some(expected) = expected {
+                        let verification = verify(result, expected).to_string();
+                        record.push(("verification", Value::from(verification)));
+                    }
+                    record
+                })
+                .collect();
+            write_records(format, &records);
+        }
+    }
+}
+
+/// Prints the answers for several parts of one day, followed by the time to parse the input once
+/// and the time to solve each part.
+fn print_parts(results: &[RunResult], expected: Option<&AnswerStore>) {
+    for result in results {
+        match result.solution {
+            Ok(ref solution) => println!("Part {}: {}", result.part, solution),
+            Err(ref e) => {
+                eprintln!("Unable to solve problem {}.{}: {}", result.day, result.part, e);
+            }
+        }
+    }
+    print_timings(results);
+    for result in results {
+        if let Some(peak_rss) = result.peak_rss {
+            println!("Peak RSS part {}: {}", result.part, sandbox::format_size(peak_rss));
+        }
+    }
+    if let Some(expected) = expected {
+        for result in results {
+            println!("Verification part {}: {}", result.part, verify(result, expected));
+        }
+    }
+}
+
+/// Prints the time it took to parse the input and to solve each part, with the allocations
+/// made if they were counted.
+fn print_timings(results: &[RunResult]) {
+    let parse = results.iter().find(|result| result.parse_time.is_some());
+    println!("Time to parse: {}{}",
+             format_optional_duration(parse.and_then(|result| result.parse_time)),
+             format_allocs(parse.and_then(|result| result.parse_allocs)));
+    for result in results {
+        println!("Time to solve part {}: {}{}",
+                 result.part,
+                 format_duration(&result.solve_time),
+                 format_allocs(result.solve_allocs));
+    }
+}
+
+/// The fields of a record with the allocation counts, under the given names for the number of
+/// allocations, bytes allocated and peak bytes.
+fn alloc_fields(names: [&'static str; 3], allocs: Option<AllocStats>) -> Record {
+    vec![(names[0], Value::from(allocs.map(|allocs| allocs.allocations))),
+         (names[1], Value::from(allocs.map(|allocs| allocs.bytes))),
+         (names[2], Value::from(allocs.map(|allocs| allocs.peak_bytes)))]
+}
+
+/// Writes structured records to stdout in the given format.
+fn write_records(format: Format, records: &[Record]) {
+    let stdout = io::stdout();
+    let result = match format {
+        Format::Json => output::write_json(stdout.lock(), records),
+        Format::Csv => output::write_csv(stdout.lock(), records),
+        Format::Plain => unreachable!("Plain output is not made of records"),
+    };
+    if let Err(e) = result {
+        eprintln!("Unable to write output: {}", e);
         process::exit(1);
-    });
+    }
+}
+
+/// Prints a table with one row per result. If `expected` is given every row is also verified
+/// against it.
+fn print_summary(results: &[RunResult], expected: Option<&AnswerStore>) {
+    let answers: Vec<String> = results.iter()
+        .map(|result| match result.solution {
+            Ok(ref solution) => single_line(solution),
+            Err(ref e) => format!("error: {}", e),
+        })
+        .collect();
+    let answer_width = answers.iter().map(|a| a.len()).max().unwrap_or(0).max("Answer".len());
 
-    let time = solution_timer.elapsed();
-    println!("Solution: {}\nTime to solve: {}",
-             solution,
-             format_duration(&time));
+    let sandboxed = results.iter().any(|result| result.peak_rss.is_some());
+    let mut header = format!("Day  Part  {:<width$}  {:<10}  {:<10}",
+                             "Answer",
+                             "Parse",
+                             "Solve",
+                             width = answer_width);
+    let counted = results.iter().any(|result| result.solve_allocs.is_some());
+    if counted {
+        header.push_str(&format!("  {:>11}  {:>10}  {:>10}", "Allocations", "Allocated", "Peak"));
+    }
+    if sandboxed {
+        header.push_str(&format!("  {:<10}", "Peak RSS"));
+    }
+    if expected.is_some() {
+        header.push_str("  Verification");
+    }
+    println!("{}", header.trim_end());
+    let mut total = Duration::new(0, 0);
+    for (result, answer) in results.iter().zip(answers.iter()) {
+        let mut row = format!("{:>3}  {:>4}  {:<width$}  {:<10}  {:<10}",
+                              result.day,
+                              result.part.to_string(),
+                              answer,
+                              format_optional_duration(result.parse_time),
+                              format_duration(&result.solve_time),
+                              width = answer_width);
+        if counted {
+            row.push_str(&format_alloc_columns(result.solve_allocs));
+        }
+        if sandboxed {
+            row.push_str(&format!("  {:<10}", format_optional_size(result.peak_rss)));
+        }
+        if let Some(expected) = expected {
+            row.push_str(&format!("  {}", verify(result, expected)));
+        }
+        println!("{}", row.trim_end());
+        total += result.parse_time.unwrap_or_default() + result.solve_time;
+    }
+    println!("Total time: {}", format_duration(&total));
 }
 
-#[cfg(feature = "bench")]
-fn benchmark(solver: Box<ProblemSolver>, part: Part, input: String) {
-    use test::{fmt_bench_samples, bench, black_box, Bencher};
-    let samples = bench::benchmark(|b: &mut Bencher| {
-        b.iter(|| solver.solve(part, black_box(input.clone())).unwrap())
-    });
-    println!("{}", fmt_bench_samples(&samples));
+/// Prints the days that have a solver, in the format selected by the options.
+fn print_solver_list(options: &Options) {
+    let registry = solver_registry();
+    let solvers: Vec<_> = registry.registrations()
+        .map(|registration| (registration, (registration.create)()))
+        .collect();
+    match options.format {
+        Format::Plain if options.quiet => {
+            for (registration, _) in solvers {
+                println!("{}", registration.day);
+            }
+        }
+        Format::Plain => {
+            println!("Year  Day  Parts  Crate  Title");
+            for (registration, solver) in solvers {
+                println!("{:>4}  {:>3}  {:<5}  {:<5}  {}",
+                         registration.year,
+                         registration.day,
+                         format_parts(solver.parts()),
+                         registration.crate_name,
+                         solver.title());
+                for spec in solver.params() {
+                    println!("{:25}--param {}=<{}> (default {}): {}",
+                             "",
+                             spec.name,
+                             spec.kind,
+                             spec.default,
+                             spec.help);
+                }
+            }
+        }
+        format => {
+            let records: Vec<Record> = solvers.iter()
+                .map(|&(registration, ref solver)| {
+                    vec![("year", Value::UInt(registration.year as u64)),
+                         ("day", Value::UInt(registration.day as u64)),
+                         ("title", Value::from(solver.title())),
+                         ("parts", parts_value(solver.parts())),
+                         ("crate", Value::from(registration.crate_name)),
+                         ("params",
+                          Value::List(solver.params()
+                              .iter()
+                              .map(|spec| Value::from(spec.name))
+                              .collect()))]
+                })
+                .collect();
+            write_records(format, &records);
+        }
+    }
 }
 
-#[cfg(not(feature = "bench"))]
-fn benchmark(_solver: Box<ProblemSolver>, _part: Part, _input: String) {}
+/// How far along the solution for one day of the calendar is.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum DayState {
+    /// There is no solver for the day.
+    Missing,
+    /// There is a solver, but its answers have not been verified against recorded answers.
+    Implemented,
+    /// Every part is solved and matches the recorded answer.
+    Verified,
+    /// Some part fails to solve or does not match the recorded answer.
+    Failing,
+}
 
-fn parse_arguments() -> Result<(u8, Part, String, bool), String> {
+impl fmt::Display for DayState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+               "{}",
+               match *self {
+                   DayState::Missing => "missing",
+                   DayState::Implemented => "implemented",
+                   DayState::Verified => "verified",
+                   DayState::Failing => "FAILING",
+               })
+    }
+}
+
+/// The status of one day of the calendar.
+struct DayStatus {
+    day: u8,
+    state: DayState,
+    title: Option<&'static str>,
+    parts: &'static [Part],
+    /// If the input file for the day exists.
+    has_input: bool,
+    /// If expected answers are recorded for all parts the solver supports.
+    has_answers: bool,
+}
+
+/// Computes the status of all 25 days. Days with a solver, an input and recorded answers are
+/// solved to see if they are verified.
+fn day_statuses(options: &Options, answers: &AnswerStore) -> Vec<DayStatus> {
+    let registry = solver_registry();
+    (1..26)
+        .map(|day| {
+            let solver = match registry.get(YEAR, day) {
+                Ok(solver) => solver,
+                Err(_) => {
+                    return DayStatus {
+                        day,
+                        state: DayState::Missing,
+                        title: None,
+                        parts: &[],
+                        has_input: false,
+                        has_answers: false,
+                    }
+                }
+            };
+            let input_path = default_input_path(&options.inputs_dir, day);
+            let has_input = Path::new(&input_path).is_file();
+            let parts = supported_parts(options.part, &*solver);
+            let has_answers =
+                parts.iter().all(|&part| answers.get(day, part, &input_path).is_some());
+            let state = match read_input(&input_path) {
+                Ok(ref input) if has_answers => {
+                    let results = solve_parts(options, day, &parts, &input_path, input);
+                    if results.iter().all(|result| verify(result, answers).is_pass()) {
+                        DayState::Verified
+                    } else {
+                        DayState::Failing
+                    }
+                }
+                _ => DayState::Implemented,
+            };
+            DayStatus {
+                day,
+                state,
+                title: Some(solver.title()),
+                parts: solver.parts(),
+                has_input,
+                has_answers,
+            }
+        })
+        .collect()
+}
+
+/// Prints the status of every day in the format selected by the options.
+fn print_status(options: &Options, statuses: &[DayStatus]) {
+    let yes_no = |b| if b { "yes" } else { "no" };
+    match options.format {
+        Format::Plain if options.quiet => {
+            for status in statuses {
+                println!("{}", status.state);
+            }
+        }
+        Format::Plain => {
+            println!("Day  Status       Parts  Input  Answers  Title");
+            for status in statuses {
+                if status.state == DayState::Missing {
+                    println!("{:>3}  {}", status.day, status.state);
+                    continue;
+                }
+                println!("{:>3}  {:<11}  {:<5}  {:<5}  {:<7}  {}",
+                         status.day,
+                         status.state.to_string(),
+                         format_parts(status.parts),
+                         yes_no(status.has_input),
+                         yes_no(status.has_answers),
+                         status.title.unwrap_or(""));
+            }
+            let count = |state| statuses.iter().filter(|status| status.state == state).count();
+            println!("Verified: {}, implemented: {}, failing: {}, missing: {}",
+                     count(DayState::Verified),
+                     count(DayState::Implemented),
+                     count(DayState::Failing),
+                     count(DayState::Missing));
+        }
+        format => {
+            let records: Vec<Record> = statuses.iter()
+                .map(|status| {
+                    vec![("day", Value::UInt(status.day as u64)),
+                         ("status", Value::from(status.state.to_string().to_lowercase())),
+                         ("title", Value::from(status.title)),
+                         ("parts", parts_value(status.parts)),
+                         ("has_input", Value::from(status.has_input)),
+                         ("has_answers", Value::from(status.has_answers))]
+                })
+                .collect();
+            write_records(format, &records);
+        }
+    }
+}
+
+/// The parse errors found in the input for one day.
+struct CheckResult {
+    day: u8,
+    input_path: String,
+    input: String,
+    errors: Result<Vec<ParseError>, Error>,
+}
+
+/// Parses the inputs selected by the options without solving anything, and prints every error
+/// found in them. Returns the code the program should exit with.
+fn run_check(options: &Options) -> i32 {
+    let days = if options.all {
+        get_all_problem_solvers(options)
+            .into_iter()
+            .map(|(day, solver)| (day, Ok(solver), default_input_path(&options.inputs_dir, day)))
+            .collect()
+    } else {
+        let day = options.day.unwrap();
+        let input_path = options.input_path.clone().unwrap();
+        vec![(day, configured_solver(day, options.params(day)), input_path)]
+    };
+    let results: Vec<CheckResult> = days.into_iter()
+        .map(|(day, solver, input_path)| {
+            let input = read_input(&input_path);
+            let errors = match (solver, input.as_ref()) {
+                (Ok(solver), Ok(input)) => Ok(solver.check(input)),
+                (Err(e), _) => Err(e),
+                (_, Err(e)) => Err(e.clone()),
+            };
+            CheckResult {
+                day,
+                input_path,
+                input: input.unwrap_or_default(),
+                errors,
+            }
+        })
+        .collect();
+
+    report_check(options, &results);
+    let mut code = 0;
+    for result in &results {
+        match result.errors {
+            Ok(ref errors) if errors.is_empty() => (),
+            Ok(ref errors) => code = exit_code(&Error::Parse(errors[0].clone())),
+            Err(ref e) if code == 0 => code = exit_code(e),
+            Err(_) => (),
+        }
+    }
+    code
+}
+
+/// Prints the parse errors in the format selected by the options.
+fn report_check(options: &Options, results: &[CheckResult]) {
+    match options.format {
+        Format::Plain => {
+            for result in results {
+                match result.errors {
+                    Ok(ref errors) if errors.is_empty() => {
+                        if !options.quiet {
+                            println!("{}: ok", result.input_path);
+                        }
+                    }
+                    Ok(ref errors) => {
+                        for error in errors {
+                            print!("{}",
+                                   check::format_diagnostic(&result.input_path,
+                                                            &result.input,
+                                                            error));
+                        }
+                    }
+                    Err(ref e) => {
+                        eprintln!("Unable to check day {}: {}", result.day, e);
+                    }
+                }
+            }
+        }
+        format => {
+            let mut records: Vec<Record> = vec![];
+            for result in results {
+                let errors = match result.errors {
+                    Ok(ref errors) => {
+                        errors.iter().map(|e| (e.message().to_owned(), e.span())).collect()
+                    }
+                    Err(ref e) => vec![(e.to_string(), None)],
+                };
+                for (message, span) in errors {
+                    records.push(vec![("day", Value::UInt(result.day as u64)),
+                                      ("input", Value::from(result.input_path.as_str())),
+                                      ("line", Value::from(span.map(|s| s.line as u64))),
+                                      ("column", Value::from(span.map(|s| s.column as u64))),
+                                      ("start", Value::from(span.map(|s| s.start as u64))),
+                                      ("end", Value::from(span.map(|s| s.end as u64))),
+                                      ("error", Value::from(message))]);
+                }
+            }
+            write_records(format, &records);
+        }
+    }
+}
+
+/// The outcome of benchmarking one stage of one day.
+struct BenchResult {
+    day: u8,
+    stage: Stage,
+    input_path: String,
+    input_hash: u64,
+    /// The hash of the parameters the solver was configured with.
+    params_hash: u64,
+    stats: Result<Stats, Error>,
+    /// The allocations made by one run of the stage, if they were counted.
+    allocs: Option<AllocStats>,
+}
+
+/// Runs the benchmarks selected by the options, prints them and compares them against and saves
+/// them to baselines if asked to. Returns the code the program should exit with, which is not
+/// zero if any benchmark regressed compared to the baseline or if the single selected day
+/// failed.
+fn run_benchmarks(options: &Options) -> Result<i32, String> {
+    let results = if options.all {
+        benchmark_all(options)
+    } else {
+        let day = options.day.unwrap();
+        let solver = match configured_solver(day, options.params(day)) {
+            Ok(solver) => solver,
+            Err(e) => {
+                eprintln!("Error with problem solver: {}", e);
+                return Ok(exit_code(&e));
+            }
+        };
+        let input_path = options.input_path.as_ref().unwrap();
+        let input = match read_input(input_path) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("{}", e);
+                return Ok(exit_code(&e));
+            }
+        };
+        let parts = selected_parts(options.part, &*solver);
+        benchmark_day(options, &*solver, day, &parts, input_path, &input)
+    };
+
+    let comparisons: Vec<Option<Comparison>> = match options.compare_baseline {
+        Some(ref name) => {
+            let baseline = Baseline::load(&options.baselines_dir, name)?;
+            results.iter()
+                .map(|result| {
+                    result.stats.as_ref().ok().map(|stats| {
+                        let old = baseline.get(result.day,
+                                               result.stage,
+                                               result.input_hash,
+                                               result.params_hash);
+                        Comparison::new(old, stats, options.noise_threshold)
+                    })
+                })
+                .collect()
+        }
+        None => results.iter().map(|_| None).collect(),
+    };
+
+    report_benchmarks(options, &results, &comparisons);
+
+    if let Some(ref name) = options.save_baseline {
+        let mut baseline = Baseline::load(&options.baselines_dir, name)?;
+        for result in &results {
+            if let Ok(ref stats) = result.stats {
+                baseline.insert(result.day,
+                                result.stage,
+                                result.input_hash,
+                                result.params_hash,
+                                stats.clone());
+            }
+        }
+        baseline.save(&options.baselines_dir, name)
+            .map_err(|e| format!("Unable to save baseline {}: {}", name, e))?;
+    }
+    let failure = results.iter().filter_map(|result| result.stats.as_ref().err()).next();
+    let regressed = comparisons.iter().any(|c| c.map(|c| c.is_regression()).unwrap_or(false));
+    match failure {
+        Some(e) if !options.all => Ok(exit_code(e)),
+        _ if regressed => Ok(EXIT_FAILURE),
+        _ => Ok(0),
+    }
+}
+
+/// Benchmarks parsing the input and then solving each of the given parts separately. Each stage
+/// is run once before it is benchmarked so a failure is reported instead of being benchmarked.
+/// `solver` is only used for its parameter declarations, the results are keyed by the
+/// parameters in the options.
+///
+/// Like when solving, the stages run on their own thread, so a panic or running past the
+/// timeout only fails the stage it happens in. The parts after it are benchmarked on a new
+/// thread.
+fn benchmark_day(options: &Options,
+                 solver: &dyn DynProblemSolver,
+                 day: u8,
+                 parts: &[Part],
+                 input_path: &str,
+                 input: &str)
+                 -> Vec<BenchResult> {
+    let params = options.params(day);
+    let params_hash = Params::new(solver.params(), params)
+        .map(|params| baseline::params_hash(&params))
+        .unwrap_or_else(|e| panic!("Invalid parameters for day {}: {}", day, e));
+    let result = |stage, stats, allocs| {
+        BenchResult {
+            day,
+            stage,
+            input_path: input_path.to_owned(),
+            input_hash: baseline::input_hash(input),
+            params_hash,
+            stats,
+            allocs,
+        }
+    };
+    let config = &options.bench_config;
+    let benchmark_timeout = benchmark_timeout(config, options.timeout);
+
+    let mut results = vec![];
+    let mut remaining = parts;
+    let mut benchmark_parse = true;
+    while benchmark_parse || !remaining.is_empty() {
+        let (sender, receiver) = mpsc::channel();
+        let mut worker =
+            Some(spawn_benchmark(config, day, params, remaining, input, benchmark_parse, sender));
+        // An error means the thread panicked or is still running, so it won't send any more.
+        let mut receive = |timeout: Option<Duration>| -> Result<BenchEvent, Error> {
+            match timeout {
+                Some(timeout) => {
+                    receiver.recv_timeout(timeout).map_err(|e| match e {
+                        mpsc::RecvTimeoutError::Timeout => Error::TimedOut(timeout),
+                        mpsc::RecvTimeoutError::Disconnected => worker_panic(&mut worker),
+                    })
+                }
+                None => receiver.recv().map_err(|_| worker_panic(&mut worker)),
+            }
+        };
+        // Runs the next stage once and then, if that succeeded and it is to be benchmarked,
+        // benchmarks it. Also returns if the thread can go on with the stage after it.
+        let mut next_stage = |benchmark: bool| -> (Result<Option<Stats>, Error>, _, bool) {
+            let (ran, allocs) = match receive(options.timeout) {
+                Ok(BenchEvent::Ran(ran, allocs)) => (ran, allocs),
+                Ok(BenchEvent::Benchmarked(_)) => unreachable!("Benchmarked before running"),
+                Err(e) => return (Err(e), None, false),
+            };
+            match ran {
+                Err(e) => return (Err(e), allocs, true),
+                Ok(()) if !benchmark => return (Ok(None), allocs, true),
+                Ok(()) => (),
+            }
+            match receive(benchmark_timeout) {
+                Ok(BenchEvent::Benchmarked(stats)) => (Ok(Some(stats)), allocs, true),
+                Ok(BenchEvent::Ran(..)) => unreachable!("Ran twice before benchmarking"),
+                Err(e) => (Err(e), allocs, false),
+            }
+        };
+
+        // The thread only goes on after parsing if it succeeded.
+        let (parsed, parse_allocs, _) = next_stage(benchmark_parse);
+        if benchmark_parse {
+            let stats = parsed.clone().map(|stats| stats.expect("Parsing is benchmarked"));
+            results.push(result(Stage::Parse, stats, parse_allocs));
+            benchmark_parse = false;
+        }
+        if let Err(e) = parsed {
+            results.extend(remaining.iter()
+                .map(|&part| result(Stage::Solve(part), Err(e.clone()), None)));
+            break;
+        }
+        let mut done = 0;
+        let mut alive = true;
+        while alive && done < remaining.len() {
+            let (stats, allocs, still_alive) = next_stage(true);
+            let stats = stats.map(|stats| stats.expect("Solving is benchmarked"));
+            results.push(result(Stage::Solve(remaining[done]), stats, allocs));
+            alive = still_alive;
+            done += 1;
+        }
+        remaining = &remaining[done..];
+    }
+    results
+}
+
+/// How long to wait for a stage to be benchmarked after it ran once within `timeout`. Every
+/// sample runs the stage at least once, so that takes the warmup and measurement time plus up
+/// to one run per sample and one more for the end of the warmup.
+fn benchmark_timeout(config: &BenchConfig, timeout: Option<Duration>) -> Option<Duration> {
+    let runs = config.samples as u32 + 1;
+    timeout.and_then(|timeout| timeout.checked_mul(runs))
+        .and_then(|runs| runs.checked_add(config.warmup_time + config.measurement_time))
+}
+
+/// What the thread benchmarking a day reports back, for every stage in order.
+enum BenchEvent {
+    /// The stage ran once, with the given outcome and allocations.
+    Ran(Result<(), Error>, Option<AllocStats>),
+    /// The stage that just ran successfully was benchmarked.
+    Benchmarked(Stats),
+}
+
+/// Starts a thread that parses the input and then solves the given parts with a new solver for
+/// the day, running and benchmarking every stage and sending what happened over `sender`.
+/// Parsing is only benchmarked if `benchmark_parse` is set, but always reported as a stage.
+fn spawn_benchmark(config: &BenchConfig,
+                   day: u8,
+                   params: &[(String, String)],
+                   parts: &[Part],
+                   input: &str,
+                   benchmark_parse: bool,
+                   sender: mpsc::Sender<BenchEvent>)
+                   -> thread::JoinHandle<()> {
+    let config = config.clone();
+    let params = params.to_vec();
+    let parts = parts.to_vec();
+    let input = input.to_owned();
+    thread::Builder::new()
+        .name(isolate::SOLVER_THREAD.to_owned())
+        .spawn(move || {
+            let solver = match configured_solver(day, &params) {
+                Ok(solver) => solver,
+                Err(e) => {
+                    let _ = sender.send(BenchEvent::Ran(Err(e), None));
+                    return;
+                }
+            };
+            let (parsed_input, parse_allocs) = allocations::measure(|| solver.parse(&input));
+            let parsed_input = match parsed_input {
+                Ok(parsed_input) => parsed_input,
+                Err(e) => {
+                    let _ = sender.send(BenchEvent::Ran(Err(e), parse_allocs));
+                    return;
+                }
+            };
+            let _ = sender.send(BenchEvent::Ran(Ok(()), parse_allocs));
+            if benchmark_parse {
+                let stats = bench::benchmark(&config, || solver.parse(&input));
+                let _ = sender.send(BenchEvent::Benchmarked(stats));
+            }
+            for part in parts {
+                let (solution, solve_allocs) =
+                    allocations::measure(|| solver.solve(part, &parsed_input));
+                let ok = solution.is_ok();
+                if sender.send(BenchEvent::Ran(solution.map(|_| ()), solve_allocs)).is_err() {
+                    return;
+                }
+                if ok {
+                    let stats = bench::benchmark(&config, || solver.solve(part, &parsed_input));
+                    if sender.send(BenchEvent::Benchmarked(stats)).is_err() {
+                        return;
+                    }
+                }
+            }
+        })
+        .expect("Unable to start benchmark thread")
+}
+
+/// Benchmarks parsing and the selected parts of every day that has a solver.
+fn benchmark_all(options: &Options) -> Vec<BenchResult> {
+    let mut results = vec![];
+    for (day, solver) in get_all_problem_solvers(options) {
+        let input_path = default_input_path(&options.inputs_dir, day);
+        match read_input(&input_path) {
+            Ok(input) => {
+                let parts = supported_parts(options.part, &*solver);
+                results.extend(benchmark_day(options, &*solver, day, &parts, &input_path, &input))
+            }
+            Err(e) => {
+                results.push(BenchResult {
+                    day,
+                    stage: Stage::Parse,
+                    input_path: input_path.clone(),
+                    input_hash: 0,
+                    params_hash: 0,
+                    stats: Err(e),
+                    allocs: None,
+                })
+            }
+        }
+    }
+    results
+}
+
+/// Prints the benchmark results in the format selected by the options.
+fn report_benchmarks(options: &Options,
+                     results: &[BenchResult],
+                     comparisons: &[Option<Comparison>]) {
+    match options.format {
+        Format::Plain if options.quiet => {
+            for result in results {
+                match result.stats {
+                    Ok(ref stats) => println!("{:.0}", stats.median),
+                    Err(ref e) => {
+                        eprintln!("Unable to benchmark day {}: {}", result.day, e);
+                    }
+                }
+            }
+        }
+        Format::Plain if options.all => {
+            print_benchmark_summary(results, comparisons, options.compare_baseline.is_some())
+        }
+        Format::Plain => {
+            for (result, comparison) in results.iter().zip(comparisons) {
+                let stage = match result.stage {
+                    Stage::Parse => "Parse".to_owned(),
+                    Stage::Solve(part) => format!("Solve part {}", part),
+                };
+                match result.stats {
+                    Ok(ref stats) => {
+                        println!("{}: {}{}", stage, stats, format_allocs(result.allocs))
+                    }
+                    Err(ref e) => {
+                        eprintln!("{}: Unable to benchmark day {}: {}", stage, result.day, e);
+                    }
+                }
+                if let Some(ref comparison) = *comparison {
+                    println!("{}: Change {}", stage, comparison);
+                }
+            }
+        }
+        format => {
+            let records: Vec<Record> = results.iter()
+                .zip(comparisons)
+                .map(|(result, comparison)| {
+                    let stats = result.stats.as_ref().ok();
+                    let (stage, part) = match result.stage {
+                        Stage::Parse => ("parse", None),
+                        Stage::Solve(part) => ("solve", Some(part_number(part))),
+                    };
+                    let mut record = vec![("day", Value::UInt(result.day as u64)),
+                                          ("stage", Value::from(stage)),
+                                          ("part", Value::from(part)),
+                                          ("input", Value::from(result.input_path.as_str())),
+                                          ("error",
+                                           Value::from(result.stats
+                                               .as_ref()
+                                               .err()
+                                               .map(|e| e.to_string()))),
+                                          ("median_ns", Value::from(stats.map(|s| s.median))),
+                                          ("mean_ns", Value::from(stats.map(|s| s.mean))),
+                                          ("std_dev_ns", Value::from(stats.map(|s| s.std_dev))),
+                                          ("min_ns", Value::from(stats.map(|s| s.min))),
+                                          ("max_ns", Value::from(stats.map(|s| s.max))),
+                                          ("samples", Value::from(stats.map(|s| s.samples as u64))),
+                                          ("outliers",
+                                           Value::from(stats.map(|s| s.outliers as u64))),
+                                          ("iterations_per_sample",
+                                           Value::from(stats.map(|s| s.iterations_per_sample)))];
+                    if options.count_allocations {
+                        record.extend(alloc_fields(["allocations",
+                                                    "allocated_bytes",
+                                                    "peak_bytes"],
+                                                   result.allocs));
+                    }
+                    if options.compare_baseline.is_some() {
+                        record.push(("baseline_change_percent",
+                                     Value::from(comparison.and_then(|c| c.change()))));
+                        record.push(("baseline_comparison",
+                                     Value::from(comparison.map(|c| c.to_string()))));
+                    }
+                    record
+                })
+                .collect();
+            write_records(format, &records);
+        }
+    }
+}
+
+fn print_benchmark_summary(results: &[BenchResult],
+                           comparisons: &[Option<Comparison>],
+                           compare: bool) {
+    print!("Day  Stage  {:>12}  {:>12}  {:>12}  {:>12}  {:>12}",
+           "Median ns",
+           "Mean ns",
+           "Std dev ns",
+           "Min ns",
+           "Max ns");
+    let counted = results.iter().any(|result| result.allocs.is_some());
+    if counted {
+        print!("  {:>11}  {:>10}  {:>10}", "Allocations", "Allocated", "Peak");
+    }
+    if compare {
+        print!("  Change");
+    }
+    println!();
+    for (result, comparison) in results.iter().zip(comparisons) {
+        match result.stats {
+            Ok(ref stats) => {
+                print!("{:>3}  {:>5}  {:>12}  {:>12}  {:>12}  {:>12}  {:>12}",
+                       result.day,
+                       result.stage.to_string(),
+                       bench::format_ns(stats.median),
+                       bench::format_ns(stats.mean),
+                       bench::format_ns(stats.std_dev),
+                       bench::format_ns(stats.min),
+                       bench::format_ns(stats.max));
+                if counted {
+                    print!("{}", format_alloc_columns(result.allocs));
+                }
+                if let Some(ref comparison) = *comparison {
+                    print!("  {}", comparison);
+                }
+                println!();
+            }
+            Err(ref e) => {
+                println!("{:>3}  {:>5}  error: {}", result.day, result.stage.to_string(), e)
+            }
+        }
+    }
+}
+
+fn parse_arguments() -> Result<Options, Error> {
     let app = create_app();
     let matches = app.clone().get_matches();
+    let command = match matches.subcommand_name() {
+        Some("list") => Command::List,
+        Some("status") => Command::Status,
+        _ => Command::Run,
+    };
+
+    let day = match matches.value_of("day") {
+        Some(_) => {
+            let day = value_t!(matches.value_of("day"), u8)
+                .map_err(|e| Error::InvalidArgument(e.to_string()))?;
+            if !(1..=25).contains(&day) {
+                return Err(Error::InvalidArgument("Day must be 1-25".to_owned()));
+            }
+            Some(day)
+        }
+        None => None,
+    };
+    let part = match matches.value_of("part").unwrap() {
+        "both" | "all" => None,
+        part => Some(Part::from_str(part)?),
+    };
+    let check_params = |day, params: &[(String, String)]| {
+        configured_solver(day, params).map(|_| ()).map_err(|e| e.to_string())
+    };
+    let config = match matches.value_of("config") {
+        Some(path) => Config::load(Path::new(path), &check_params),
+        None => Config::discover(&check_params),
+    };
+    let config = config.map_err(Error::InvalidArgument)?;
+    let mut params = config.params;
+
+    let noise_threshold = match matches.value_of("noise_threshold") {
+        Some(_) => {
+            value_t!(matches.value_of("noise_threshold"), f64)
+                .map_err(|e| Error::InvalidArgument(e.to_string()))?
+        }
+        None => config.noise_threshold.unwrap_or(DEFAULT_NOISE_THRESHOLD),
+    };
+    let format = match matches.value_of("output") {
+        Some(format) => Format::from_str(format).map_err(Error::InvalidArgument)?,
+        None => config.output.unwrap_or(Format::Plain),
+    };
+    let mut bench_config = BenchConfig::default();
+    if let Some(ms) = config.warmup_ms {
+        bench_config.warmup_time = Duration::from_millis(ms);
+    }
+    if let Some(ms) = config.measurement_ms {
+        bench_config.measurement_time = Duration::from_millis(ms);
+    }
+    if let Some(samples) = config.samples {
+        bench_config.samples = samples;
+    }
+    if let (Some(day), Some(values)) = (day, matches.values_of("param")) {
+        for value in values {
+            params.entry(day).or_default().push(base::parse_assignment(value)?);
+        }
+    }
+    let quiet = matches.is_present("quiet");
+    if quiet && format != Format::Plain {
+        return Err(Error::InvalidArgument("--quiet can only be used with plain output".to_owned()));
+    }
+    let timeout = match matches.value_of("timeout") {
+        Some(timeout) => Some(isolate::parse_duration(timeout).map_err(Error::InvalidArgument)?),
+        None => config.timeout,
+    };
+    let sandbox = if matches.is_present("sandbox") {
+        if !cfg!(target_os = "linux") {
+            return Err(Error::InvalidArgument("--sandbox is only supported on Linux".to_owned()));
+        }
+        let cpu_time = matches.value_of("cpu_limit")
+            .map(isolate::parse_duration)
+            .unwrap_or(Ok(Duration::from_secs(DEFAULT_CPU_LIMIT_SECS)))
+            .map_err(Error::InvalidArgument)?;
+        let memory = matches.value_of("memory_limit")
+            .map(sandbox::parse_size)
+            .unwrap_or(Ok(DEFAULT_MEMORY_LIMIT))
+            .map_err(Error::InvalidArgument)?;
+        Some(sandbox::Limits { cpu_time, memory })
+    } else {
+        None
+    };
+    let watch = matches.is_present("watch");
+    if watch && format != Format::Plain {
+        return Err(Error::InvalidArgument("--watch can only be used with plain output".to_owned()));
+    }
+    if watch && matches.value_of("input") == Some(STDIN_PATH) {
+        return Err(Error::InvalidArgument("--watch can not watch stdin".to_owned()));
+    }
+
+    let setting = |name, config_value: Option<String>, default: &str| {
+        matches.value_of(name)
+            .map(|value| value.to_owned())
+            .or(config_value)
+            .unwrap_or_else(|| default.to_owned())
+    };
+    let inputs_dir = setting("inputs_dir", config.inputs_dir, "inputs");
+    let input_path = day.map(|day| match matches.value_of("input") {
+        Some(input_path) => input_path.to_owned(),
+        None => default_input_path(&inputs_dir, day),
+    });
+
+    Ok(Options {
+        command,
+        day,
+        part,
+        input_path,
+        inputs_dir,
+        answers_path: setting("answers", config.answers, "answers"),
+        all: matches.is_present("all"),
+        check: matches.is_present("check"),
+        bench: matches.is_present("bench"),
+        verify: matches.is_present("verify"),
+        record: matches.is_present("record"),
+        baselines_dir: setting("baselines_dir", config.baselines_dir, "baselines"),
+        save_baseline: matches.value_of("save_baseline").map(|s| s.to_owned()),
+        compare_baseline: matches.value_of("baseline").map(|s| s.to_owned()),
+        noise_threshold,
+        bench_config,
+        format,
+        quiet,
+        count_allocations: matches.is_present("alloc_stats"),
+        sandbox,
+        timeout,
+        watch,
+        watch_paths: matches.values_of("watch_path")
+            .map(|paths| paths.map(|path| path.to_owned()).collect())
+            .unwrap_or_default(),
+        params,
+    })
+}
+
+/// Reads the whole input, from stdin if the path is `-` and otherwise from the file at the path.
+/// The input is normalized with `base::input::normalize`. The returned error tells which file
+/// could not be read.
+fn read_input(input_path: &str) -> Result<String, Error> {
+    let read = || -> io::Result<String> {
+        let mut input_data = String::new();
+        if input_path == STDIN_PATH {
+            io::stdin().read_to_string(&mut input_data)?;
+        } else {
+            File::open(input_path)?.read_to_string(&mut input_data)?;
+        }
+        Ok(input_data)
+    };
+    read().map(|input| base::input::normalize(&input)).map_err(|e| {
+        let message = format!("Unable to read input from {}: {}", input_path, e);
+        Error::from(io::Error::new(e.kind(), message))
+    })
+}
+
+/// The path of the input for the given day in the inputs directory.
+fn default_input_path(inputs_dir: &str, day: u8) -> String {
+    Path::new(inputs_dir).join(day.to_string()).to_string_lossy().into_owned()
+}
+
+/// Returns a registry with the solvers of all day crates.
+fn solver_registry() -> Registry {
+    let mut registry = Registry::new();
+    register_solvers(&mut registry);
+    registry
+}
+
+fn get_problem_solver(day: u8) -> Result<Box<dyn DynProblemSolver>, Error> {
+    solver_registry().get(YEAR, day)
+}
+
+/// Returns the solver for the given day, configured with the given parameter values.
+fn configured_solver(day: u8,
+                     params: &[(String, String)])
+                     -> Result<Box<dyn DynProblemSolver>, Error> {
+    let mut solver = get_problem_solver(day)?;
+    let params = Params::new(solver.params(), params)?;
+    solver.configure(&params)?;
+    Ok(solver)
+}
+
+/// Returns the solvers for all days that have one, in order, configured with the parameters in
+/// the options. The parameters were validated when the options were parsed.
+fn get_all_problem_solvers(options: &Options) -> Vec<(u8, Box<dyn DynProblemSolver>)> {
+    solver_registry()
+        .days(YEAR)
+        .into_iter()
+        .map(|day| {
+            let solver = configured_solver(day, options.params(day))
+                .unwrap_or_else(|e| panic!("Invalid parameters for day {}: {}", day, e));
+            (day, solver)
+        })
+        .collect()
+}
+
+/// Returns the parts to solve. The given part if one was selected, otherwise all parts the
+/// solver supports.
+fn selected_parts(part: Option<Part>, solver: &dyn DynProblemSolver) -> Vec<Part> {
+    match part {
+        Some(part) => vec![part],
+        None => solver.parts().to_vec(),
+    }
+}
+
+/// Returns the parts of the solver to run for every day. Like `selected_parts`, but a day that
+/// does not have the selected part gets no parts instead of an unsupported part.
+fn supported_parts(part: Option<Part>, solver: &dyn DynProblemSolver) -> Vec<Part> {
+    solver.parts().iter().cloned().filter(|&p| part.is_none_or(|part| part == p)).collect()
+}
+
+/// Formats the parts as their numbers separated by spaces.
+fn format_parts(parts: &[Part]) -> String {
+    parts.iter().map(|part| part.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+fn parts_value(parts: &[Part]) -> Value {
+    Value::List(parts.iter().map(|&part| Value::UInt(part_number(part))).collect())
+}
 
-    let day = value_t!(matches.value_of("day"), u8).unwrap();
-    if day < 1 || day > 25 {
-        return Err("Day must be 1-25".to_owned());
+fn part_number(part: Part) -> u64 {
+    match part {
+        Part::One => 1,
+        Part::Two => 2,
     }
-    let part = Part::from_str(matches.value_of("part").unwrap())?;
-    let input_path = matches.value_of("input").unwrap().to_owned();
+}
 
-    let bench = matches.is_present("bench");
+fn duration_ns(duration: &Duration) -> u64 {
+    duration.as_secs() * 1_000_000_000 + duration.subsec_nanos() as u64
+}
 
-    Ok((day, part, input_path, bench))
+/// Formats the solution on a single line, with line breaks escaped, so it fits in a table.
+fn single_line(solution: &Solution) -> String {
+    solution.to_string().replace('\n', "\\n")
 }
 
-fn read_input(input_path: &str) -> io::Result<String> {
-    let mut input_data = String::new();
-    let mut f = File::open(input_path)?;
-    f.read_to_string(&mut input_data)?;
-    Ok(input_data)
+/// Formats the allocation stats to follow a time, or nothing if there are none.
+fn format_allocs(allocs: Option<AllocStats>) -> String {
+    allocs.map(|allocs| format!(" ({})", allocs)).unwrap_or_default()
 }
 
-fn get_problem_solver(day: u8) -> Result<Box<ProblemSolver>, String> {
-    match day {
-        1 => Ok(day1::get_solver()),
-        2 => Ok(day2::get_solver()),
-        3 => Ok(day3::get_solver()),
-        _ => Err(format!("No solver for day {}", day)),
+/// Formats the allocation stats as the columns of a table, or dashes if there are none.
+fn format_alloc_columns(allocs: Option<AllocStats>) -> String {
+    match allocs {
+        Some(allocs) => {
+            format!("  {:>11}  {:>10}  {:>10}",
+                    allocs.allocations,
+                    sandbox::format_size(allocs.bytes),
+                    sandbox::format_size(allocs.peak_bytes))
+        }
+        None => format!("  {:>11}  {:>10}  {:>10}", "-", "-", "-"),
     }
 }
 
+/// Formats the size in bytes, or a dash if there is none.
+fn format_optional_size(size: Option<u64>) -> String {
+    size.map(sandbox::format_size).unwrap_or_else(|| "-".to_owned())
+}
+
+/// Formats the duration, or a dash if there is none.
+fn format_optional_duration(duration: Option<Duration>) -> String {
+    duration.map(|duration| format_duration(&duration)).unwrap_or_else(|| "-".to_owned())
+}
+
 fn format_duration(duration: &Duration) -> String {
     let us_small = duration.subsec_nanos() as u64 / 1_000;
     let us_large = duration.as_secs() * 1_000_000;
@@ -119,36 +1834,150 @@ fn format_duration(duration: &Duration) -> String {
 }
 
 fn create_app() -> App<'static, 'static> {
-    let app = App::new(APP_NAME)
+    App::new(APP_NAME)
         .version(APP_VERSION)
         .author(APP_AUTHOR)
         .about(APP_ABOUT)
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .subcommand(SubCommand::with_name("list").about("List the days that have a solver."))
+        .subcommand(SubCommand::with_name("status")
+            .about("Show which days of the calendar are verified, implemented or missing."))
         .arg(Arg::with_name("day")
             .long("day")
             .help("Select which day's problem to solve.")
             .takes_value(true)
-            .required(true))
+            .required_unless("all"))
         .arg(Arg::with_name("part")
             .long("part")
-            .help("Select which part of the problem to solve, 1 or 2.")
-            .default_value("1"))
+            .help("Select which part of the problem to solve, 1 or 2. Solves every part the \
+                   day has by default, or with both or all.")
+            .default_value("both"))
         .arg(Arg::with_name("input")
             .short("i")
             .long("input")
-            .help("Specify what problem input file to use.")
+            .help("Specify what problem input file to use, or - to read the input from stdin. \
+                   Defaults to the file named after the day in the inputs directory.")
             .takes_value(true)
-            .required(true));
-    add_nightly_args(app)
-}
-
-#[cfg(feature = "bench")]
-fn add_nightly_args(app: App<'static, 'static>) -> App<'static, 'static> {
-    app.arg(Arg::with_name("bench")
-        .long("bench")
-        .help("Activate benchmarking mode instead of just solving."))
-}
-
-#[cfg(not(feature = "bench"))]
-fn add_nightly_args(app: App<'static, 'static>) -> App<'static, 'static> {
-    app
+            .requires("day"))
+        .arg(Arg::with_name("param")
+            .long("param")
+            .value_name("KEY=VALUE")
+            .help("Set a parameter of the day's solver. Can be given multiple times. Run the \
+                   list command to see the parameters of each day.")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1))
+        .arg(Arg::with_name("all")
+            .long("all")
+            .help("Solve every part of every day that has a solver, or only the part selected \
+                   with --part, and print a summary.")
+            .conflicts_with_all(&["day", "input", "param"]))
+        .arg(Arg::with_name("inputs_dir")
+            .long("inputs-dir")
+            .help("Directory to read inputs from when no input file is given. The input for \
+                   day X is read from the file named X in this directory. [default: inputs]")
+            .takes_value(true)
+            .global(true))
+        .arg(Arg::with_name("answers")
+            .long("answers")
+            .help("The file with expected answers used by --verify and --record. [default: \
+                   answers]")
+            .takes_value(true)
+            .global(true))
+        .arg(Arg::with_name("verify")
+            .long("verify")
+            .help("Compare the solutions against the expected answers. Exits with a non-zero \
+                   code if any solution does not match or fails.")
+            .conflicts_with("record"))
+        .arg(Arg::with_name("record")
+            .long("record")
+            .help("Store the solutions as the expected answers."))
+        .arg(Arg::with_name("check")
+            .long("check")
+            .help("Only parse the input, and print every error found in it.")
+            .conflicts_with_all(&["verify", "record", "bench"]))
+        .arg(Arg::with_name("bench")
+            .long("bench")
+            .help("Activate benchmarking mode instead of just solving.")
+            .conflicts_with_all(&["verify", "record"]))
+        .arg(Arg::with_name("timeout")
+            .long("timeout")
+            .value_name("DURATION")
+            .help("Give up on parsing or solving a part after this long, such as 500ms or 2s, \
+                   and report it as timed out.")
+            .takes_value(true))
+        .arg(Arg::with_name("alloc_stats")
+            .long("alloc-stats")
+            .help("Count the allocations, bytes allocated and peak bytes in use while parsing \
+                   and solving, and show them next to the times.")
+            .conflicts_with("check"))
+        .arg(Arg::with_name("sandbox")
+            .long("sandbox")
+            .help("Solve every part in a separate process with limited CPU time and memory, \
+                   and report its peak memory use. Only supported on Linux.")
+            .conflicts_with_all(&["check", "bench"]))
+        .arg(Arg::with_name("cpu_limit")
+            .long("cpu-limit")
+            .value_name("DURATION")
+            .help("The CPU time each sandboxed part may use. [default: 60s]")
+            .takes_value(true)
+            .requires("sandbox"))
+        .arg(Arg::with_name("memory_limit")
+            .long("memory-limit")
+            .value_name("SIZE")
+            .help("The memory each sandboxed part may use, such as 512M or 2G. [default: 2G]")
+            .takes_value(true)
+            .requires("sandbox"))
+        .arg(Arg::with_name("watch")
+            .long("watch")
+            .help("Solve the day again every time the input changes, showing how the answers \
+                   changed. Restarts when the aoc executable is rebuilt.")
+            .requires("day")
+            .conflicts_with_all(&["all", "check", "bench", "verify", "record"]))
+        .arg(Arg::with_name("watch_path")
+            .long("watch-path")
+            .value_name("PATH")
+            .help("Also solve again when this file or anything in this directory changes. Can \
+                   be given multiple times.")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .requires("watch"))
+        .arg(Arg::with_name("save_baseline")
+            .long("save-baseline")
+            .help("Save the benchmark results under the given baseline name.")
+            .takes_value(true)
+            .requires("bench"))
+        .arg(Arg::with_name("baseline")
+            .long("baseline")
+            .help("Compare the benchmark results against the baseline with the given name. \
+                   Exits with a non-zero code if any benchmark regressed.")
+            .takes_value(true)
+            .requires("bench"))
+        .arg(Arg::with_name("baselines_dir")
+            .long("baselines-dir")
+            .help("Directory where benchmark baselines are stored. [default: baselines]")
+            .takes_value(true))
+        .arg(Arg::with_name("noise_threshold")
+            .long("noise-threshold")
+            .help("How many percent the median time can change compared to the baseline \
+                   before it counts as a regression or improvement. [default: 5]")
+            .takes_value(true))
+        .arg(Arg::with_name("output")
+            .long("output")
+            .help("Select the output format, plain, json or csv. [default: plain]")
+            .takes_value(true)
+            .global(true))
+        .arg(Arg::with_name("config")
+            .long("config")
+            .value_name("FILE")
+            .help("The project config file to use. By default aoc.toml is looked for in the \
+                   current directory and its parents. Command line flags override the config.")
+            .takes_value(true)
+            .global(true))
+        .arg(Arg::with_name("quiet")
+            .short("q")
+            .long("quiet")
+            .help("Print only the answers. Only valid with plain output.")
+            .global(true))
 }
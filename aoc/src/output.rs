@@ -0,0 +1,259 @@
+use base::Solution;
+
+use std::fmt::Write as FmtWrite;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// The format results are printed in.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Format {
+    /// Human readable text.
+    Plain,
+    /// A JSON array with one object per record.
+    Json,
+    /// Comma separated values with a header line.
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(Format::Plain),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Invalid output format, must be plain, json or csv, was {}", s)),
+        }
+    }
+}
+
+/// A single value in a record.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Str(String),
+    List(Vec<Value>),
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(s: &'a str) -> Self {
+        Value::Str(s.to_owned())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or(Value::Null)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<u64> for Value {
+    fn from(n: u64) -> Self {
+        Value::UInt(n)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Float(n)
+    }
+}
+
+impl From<Solution> for Value {
+    fn from(solution: Solution) -> Self {
+        match solution {
+            Solution::Int(n) => Value::Int(n),
+            Solution::UInt(n) => Value::UInt(n),
+            Solution::Text(s) => Value::Str(s),
+            Solution::Lines(lines) => Value::List(lines.into_iter().map(Value::Str).collect()),
+        }
+    }
+}
+
+/// One structured result, as a list of named fields in the order they should be printed.
+pub type Record = Vec<(&'static str, Value)>;
+
+/// Writes the records as a JSON array of objects.
+pub fn write_json<W: Write>(mut w: W, records: &[Record]) -> io::Result<()> {
+    writeln!(w, "[")?;
+    for (i, record) in records.iter().enumerate() {
+        let fields: Vec<String> = record.iter()
+            .map(|(name, value)| format!("{}: {}", json_string(name), json_value(value)))
+            .collect();
+        let separator = if i + 1 < records.len() { "," } else { "" };
+        writeln!(w, "  {{{}}}{}", fields.join(", "), separator)?;
+    }
+    writeln!(w, "]")
+}
+
+/// Writes the records as CSV. The header is taken from the field names of the first record, so
+/// all records are expected to have the same fields.
+pub fn write_csv<W: Write>(mut w: W, records: &[Record]) -> io::Result<()> {
+    if let Some(first) = records.first() {
+        let header: Vec<String> = first.iter().map(|&(name, _)| csv_string(name)).collect();
+        writeln!(w, "{}", header.join(","))?;
+    }
+    for record in records {
+        let values: Vec<String> = record.iter().map(|(_, value)| csv_value(value)).collect();
+        writeln!(w, "{}", values.join(","))?;
+    }
+    Ok(())
+}
+
+fn json_value(value: &Value) -> String {
+    match *value {
+        Value::Null => "null".to_owned(),
+        Value::Bool(b) => b.to_string(),
+        Value::Int(n) => n.to_string(),
+        Value::UInt(n) => n.to_string(),
+        Value::Float(n) if n.is_finite() => n.to_string(),
+        Value::Float(_) => "null".to_owned(),
+        Value::Str(ref s) => json_string(s),
+        Value::List(ref values) => {
+            let values: Vec<String> = values.iter().map(json_value).collect();
+            format!("[{}]", values.join(", "))
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(result, "\\u{:04x}", c as u32);
+            }
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn csv_value(value: &Value) -> String {
+    match *value {
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Int(n) => n.to_string(),
+        Value::UInt(n) => n.to_string(),
+        Value::Float(n) => n.to_string(),
+        Value::Str(ref s) => csv_string(s),
+        Value::List(ref values) => {
+            let values: Vec<String> = values.iter().map(csv_list_item).collect();
+            csv_string(&values.join("\n"))
+        }
+    }
+}
+
+/// Formats a value in a list without quoting, since the whole list is quoted as one field.
+fn csv_list_item(value: &Value) -> String {
+    match *value {
+        Value::Str(ref s) => s.clone(),
+        ref value => csv_value(value),
+    }
+}
+
+/// Quotes the string if it contains anything that would break the CSV structure.
+fn csv_string(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base::Solution;
+    use super::{write_csv, write_json, Record, Value};
+
+    fn records() -> Vec<Record> {
+        vec![vec![("day", Value::UInt(2)),
+                  ("answer", Value::from("9A7DC")),
+                  ("error", Value::Null)],
+             vec![("day", Value::UInt(3)),
+                  ("answer", Value::Null),
+                  ("error", Value::from("Bad \"input\", line 1\n"))]]
+    }
+
+    fn typed_records() -> Vec<Record> {
+        let lines = Solution::Lines(vec!["#.".to_owned(), ".#".to_owned()]);
+        vec![vec![("answer", Value::from(Solution::Int(-3)))],
+             vec![("answer", Value::from(Solution::UInt(301)))],
+             vec![("answer", Value::from(lines))]]
+    }
+
+    #[test]
+    fn json_typed_solutions() {
+        let mut out = vec![];
+        write_json(&mut out, &typed_records()).unwrap();
+        assert_eq!("[\n  {\"answer\": -3},\n  {\"answer\": 301},\n  {\"answer\": [\"#.\", \
+                    \".#\"]}\n]\n",
+                   String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn csv_typed_solutions() {
+        let mut out = vec![];
+        write_csv(&mut out, &typed_records()).unwrap();
+        assert_eq!("answer\n-3\n301\n\"#.\n.#\"\n", String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn json() {
+        let mut out = vec![];
+        write_json(&mut out, &records()).unwrap();
+        assert_eq!("[\n  {\"day\": 2, \"answer\": \"9A7DC\", \"error\": null},\n  {\"day\": 3, \
+                    \"answer\": null, \"error\": \"Bad \\\"input\\\", line 1\\n\"}\n]\n",
+                   String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn bool_values() {
+        let records = vec![vec![("ok", Value::from(true))], vec![("ok", Value::from(false))]];
+        let mut json = vec![];
+        write_json(&mut json, &records).unwrap();
+        assert_eq!("[\n  {\"ok\": true},\n  {\"ok\": false}\n]\n",
+                   String::from_utf8(json).unwrap());
+        let mut csv = vec![];
+        write_csv(&mut csv, &records).unwrap();
+        assert_eq!("ok\ntrue\nfalse\n", String::from_utf8(csv).unwrap());
+    }
+
+    #[test]
+    fn json_empty() {
+        let mut out = vec![];
+        write_json(&mut out, &[]).unwrap();
+        assert_eq!("[\n]\n", String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn csv() {
+        let mut out = vec![];
+        write_csv(&mut out, &records()).unwrap();
+        assert_eq!("day,answer,error\n2,9A7DC,\n3,,\"Bad \"\"input\"\", line 1\n\"\n",
+                   String::from_utf8(out).unwrap());
+    }
+}
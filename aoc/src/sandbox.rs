@@ -0,0 +1,558 @@
+use allocations::AllocStats;
+use answers::{decode_solution, encode_solution, escape, unescape};
+use base::{Error, ParseError, Part, Solution};
+
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The first argument that makes `aoc` run as a sandbox worker. A worker reads one `Request`
+/// from stdin, solves it and writes the `Response` to stdout.
+pub static WORKER_ARG: &str = "--sandbox-worker";
+
+/// The first line of every request, so a worker of a different version is caught.
+static PROTOCOL_VERSION: &str = "aoc-sandbox 1";
+
+/// The resource limits of a sandbox worker process.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Limits {
+    /// CPU time the process may use. Rounded up to whole seconds.
+    pub cpu_time: Duration,
+    /// Bytes of address space the process may use.
+    pub memory: u64,
+}
+
+/// What a worker is asked to solve.
+///
+/// Sent as a few `<key> <value>` lines followed by `input <length>` and the raw input:
+///
+/// ```text
+/// aoc-sandbox 1
+/// day 1
+/// part 2
+/// timeout_ns 2000000000
+/// count_allocations true
+/// param heading=E
+/// input 7
+/// R2, L3
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub day: u8,
+    pub part: Part,
+    pub params: Vec<(String, String)>,
+    pub timeout: Option<Duration>,
+    /// Whether the worker should count the allocations of parsing and solving.
+    pub count_allocations: bool,
+    pub input: String,
+}
+
+impl Request {
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "{}", PROTOCOL_VERSION)?;
+        writeln!(writer, "day {}", self.day)?;
+        writeln!(writer, "part {}", self.part)?;
+        if let Some(timeout) = self.timeout {
+            writeln!(writer, "timeout_ns {}", timeout.as_nanos())?;
+        }
+        if self.count_allocations {
+            writeln!(writer, "count_allocations true")?;
+        }
+        for (key, value) in &self.params {
+            writeln!(writer, "param {}={}", escape(key), escape(value))?;
+        }
+        writeln!(writer, "input {}", self.input.len())?;
+        writer.write_all(self.input.as_bytes())?;
+        writer.flush()
+    }
+
+    pub fn read_from<R: BufRead>(mut reader: R) -> Result<Self, String> {
+        let version = read_line(&mut reader)?;
+        if version != PROTOCOL_VERSION {
+            return Err(format!("Unsupported protocol {:?}, expected {:?}",
+                               version,
+                               PROTOCOL_VERSION));
+        }
+        let (mut day, mut part, mut timeout, mut params) = (None, None, None, vec![]);
+        let mut count_allocations = false;
+        loop {
+            let line = read_line(&mut reader)?;
+            let (key, value) = split_field(&line)?;
+            match key {
+                "day" => day = Some(parse_field(key, value)?),
+                "part" => part = Some(Part::from_str(value).map_err(|e| e.to_string())?),
+                "timeout_ns" => timeout = Some(Duration::from_nanos(parse_field(key, value)?)),
+                "count_allocations" => count_allocations = parse_field(key, value)?,
+                "param" => {
+                    let mut parts = value.splitn(2, '=');
+                    match (parts.next(), parts.next()) {
+                        (Some(key), Some(value)) => params.push((unescape(key), unescape(value))),
+                        _ => return Err(format!("Invalid param {:?}", value)),
+                    }
+                }
+                "input" => {
+                    let length = parse_field(key, value)?;
+                    let mut input = vec![0; length];
+                    reader.read_exact(&mut input)
+                        .map_err(|e| format!("Unable to read input: {}", e))?;
+                    return Ok(Request {
+                        day: day.ok_or("Missing day")?,
+                        part: part.ok_or("Missing part")?,
+                        params,
+                        timeout,
+                        count_allocations,
+                        input: String::from_utf8(input).map_err(|e| e.to_string())?,
+                    });
+                }
+                key => return Err(format!("Unknown field {:?}", key)),
+            }
+        }
+    }
+}
+
+/// What a worker found. Sent as `parse_time_ns` and `solve_time_ns` lines, optionally
+/// `parse_allocs` and `solve_allocs` lines with the allocations, bytes and peak bytes, and
+/// last either `answer <type> <answer>` or `error <kind> <message>`. The answer is written like
+/// in the answers file, so it keeps its type, and the message is escaped the same way.
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub solution: Result<Solution, Error>,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+    pub parse_allocs: Option<AllocStats>,
+    pub solve_allocs: Option<AllocStats>,
+}
+
+impl Response {
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "parse_time_ns {}", self.parse_time.as_nanos())?;
+        writeln!(writer, "solve_time_ns {}", self.solve_time.as_nanos())?;
+        for &(key, allocs) in &[("parse_allocs", self.parse_allocs),
+                                ("solve_allocs", self.solve_allocs)] {
+            if let Some(allocs) = allocs {
+                writeln!(writer,
+                         "{} {} {} {}",
+                         key,
+                         allocs.allocations,
+                         allocs.bytes,
+                         allocs.peak_bytes)?;
+            }
+        }
+        match self.solution {
+            Ok(ref solution) => writeln!(writer, "answer {}", encode_solution(solution))?,
+            Err(ref e) => {
+                let (kind, message) = encode_error(e);
+                writeln!(writer, "error {} {}", kind, escape(&message))?
+            }
+        }
+        writer.flush()
+    }
+
+    pub fn read_from<R: BufRead>(mut reader: R) -> Result<Self, String> {
+        let (mut parse_time, mut solve_time) = (None, None);
+        let (mut parse_allocs, mut solve_allocs) = (None, None);
+        loop {
+            let line = read_line(&mut reader)?;
+            let (key, value) = split_field(&line)?;
+            let solution = match key {
+                "parse_time_ns" => {
+                    parse_time = Some(Duration::from_nanos(parse_field(key, value)?));
+                    continue;
+                }
+                "solve_time_ns" => {
+                    solve_time = Some(Duration::from_nanos(parse_field(key, value)?));
+                    continue;
+                }
+                "parse_allocs" => {
+                    parse_allocs = Some(parse_alloc_stats(value)?);
+                    continue;
+                }
+                "solve_allocs" => {
+                    solve_allocs = Some(parse_alloc_stats(value)?);
+                    continue;
+                }
+                "answer" => Ok(decode_solution(value)?),
+                "error" => {
+                    let (kind, message) = split_field(value)?;
+                    Err(decode_error(kind, unescape(message))?)
+                }
+                key => return Err(format!("Unknown field {:?}", key)),
+            };
+            return Ok(Response {
+                solution,
+                parse_time: parse_time.ok_or("Missing parse time")?,
+                solve_time: solve_time.ok_or("Missing solve time")?,
+                parse_allocs,
+                solve_allocs,
+            });
+        }
+    }
+}
+
+/// Returns the kind of the error, as sent in a response, and its message.
+fn encode_error(error: &Error) -> (&'static str, String) {
+    match *error {
+        Error::Parse(ref e) => ("parse", e.to_string()),
+        Error::InvalidArgument(ref msg) => ("invalid-argument", msg.clone()),
+        Error::NoSolution(ref msg) => ("no-solution", msg.clone()),
+        Error::Unsupported(ref msg) => ("unsupported", msg.clone()),
+        Error::Io(ref e) => ("io", e.to_string()),
+        Error::Panicked(ref msg) => ("panicked", msg.clone()),
+        Error::TimedOut(limit) => ("timed-out", limit.as_nanos().to_string()),
+        Error::Killed(ref reason) => ("killed", reason.clone()),
+        Error::Overflow(ref what) => ("overflow", what.clone()),
+    }
+}
+
+/// Recreates an error sent in a response. Parse errors lose their span, but keep the position
+/// in the message.
+fn decode_error(kind: &str, message: String) -> Result<Error, String> {
+    Ok(match kind {
+        "parse" => Error::Parse(ParseError::new(message)),
+        "invalid-argument" => Error::InvalidArgument(message),
+        "no-solution" => Error::NoSolution(message),
+        "unsupported" => Error::Unsupported(message),
+        "io" => Error::from(io::Error::other(message)),
+        "panicked" => Error::Panicked(message),
+        "timed-out" => Error::TimedOut(Duration::from_nanos(parse_field(kind, &message)?)),
+        "killed" => Error::Killed(message),
+        "overflow" => Error::Overflow(message),
+        kind => return Err(format!("Unknown error kind {:?}", kind)),
+    })
+}
+
+fn read_line<R: BufRead>(reader: &mut R) -> Result<String, String> {
+    let mut line = String::new();
+    match reader.read_line(&mut line) {
+        Ok(0) => Err("Unexpected end of message".to_owned()),
+        Ok(_) => Ok(line.trim_end_matches('\n').to_owned()),
+        Err(e) => Err(format!("Unable to read message: {}", e)),
+    }
+}
+
+fn split_field(line: &str) -> Result<(&str, &str), String> {
+    let mut parts = line.splitn(2, ' ');
+    match (parts.next(), parts.next()) {
+        (Some(key), Some(value)) => Ok((key, value)),
+        _ => Err(format!("Invalid line {:?}", line)),
+    }
+}
+
+fn parse_alloc_stats(value: &str) -> Result<AllocStats, String> {
+    let fields: Vec<&str> = value.split(' ').collect();
+    if fields.len() != 3 {
+        return Err(format!("Invalid allocations {:?}", value));
+    }
+    Ok(AllocStats {
+        allocations: parse_field("allocations", fields[0])?,
+        bytes: parse_field("bytes", fields[1])?,
+        peak_bytes: parse_field("peak bytes", fields[2])?,
+    })
+}
+
+fn parse_field<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    T::from_str(value).map_err(|_| format!("Invalid {} {:?}", key, value))
+}
+
+/// The result of running one request in a worker process.
+pub struct Outcome {
+    pub response: Result<Response, Error>,
+    /// The largest resident set size the worker had, in bytes.
+    pub peak_rss: Option<u64>,
+}
+
+/// Runs the request in a new worker process started from `executable`, with the given limits.
+#[cfg(target_os = "linux")]
+pub fn run(executable: &Path, request: &Request, limits: &Limits) -> Outcome {
+    use std::io::Read;
+    use std::process::{Command, Stdio};
+    use std::os::unix::process::CommandExt;
+    use std::thread;
+
+    let cpu_seconds = limits.cpu_time.as_secs() + (limits.cpu_time.subsec_nanos() > 0) as u64;
+    let cpu_seconds = cpu_seconds.max(1);
+    let memory = limits.memory;
+    let mut command = Command::new(executable);
+    command.arg(WORKER_ARG).stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    // The soft CPU limit sends SIGXCPU, and the hard limit a second later SIGKILL in case that
+    // signal is handled.
+    unsafe {
+        command.pre_exec(move || {
+            set_limit(libc::RLIMIT_CPU, cpu_seconds, cpu_seconds + 1)?;
+            set_limit(libc::RLIMIT_AS, memory, memory)
+        });
+    }
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            let message = format!("Unable to start sandbox worker {}: {}", executable.display(), e);
+            return Outcome {
+                response: Err(Error::from(io::Error::new(e.kind(), message))),
+                peak_rss: None,
+            };
+        }
+    };
+
+    // The worker reads the whole request before writing anything, so the request can be written
+    // before reading the response. Writing fails if the worker dies early, which the exit status
+    // then tells about.
+    let _ = request.write_to(child.stdin.take().unwrap());
+    let mut stderr = child.stderr.take().unwrap();
+    let stderr_reader = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stderr.read_to_string(&mut output);
+        output
+    });
+    let mut output = vec![];
+    let _ = child.stdout.take().unwrap().read_to_end(&mut output);
+    let stderr = stderr_reader.join().unwrap_or_default();
+
+    let (status, usage) = match wait(child.id()) {
+        Ok(result) => result,
+        Err(e) => {
+            return Outcome {
+                response: Err(Error::from(e)),
+                peak_rss: None,
+            }
+        }
+    };
+    let cpu_time = timeval_duration(usage.ru_utime) + timeval_duration(usage.ru_stime);
+    let response = if libc::WIFSIGNALED(status) {
+        let cpu_exceeded = cpu_time.as_secs() >= cpu_seconds;
+        Err(Error::Killed(kill_reason(libc::WTERMSIG(status), cpu_exceeded, &stderr, limits)))
+    } else {
+        Response::read_from(&output[..]).map_err(|e| {
+            let last_line = stderr.lines().last().unwrap_or(&e);
+            Error::Killed(format!("worker exited with code {} without a result: {}",
+                                  libc::WEXITSTATUS(status),
+                                  last_line))
+        })
+    };
+    Outcome {
+        response,
+        peak_rss: Some(usage.ru_maxrss as u64 * 1024),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn run(_executable: &Path, _request: &Request, _limits: &Limits) -> Outcome {
+    Outcome {
+        response: Err(Error::Unsupported("The sandbox is only supported on Linux".to_owned())),
+        peak_rss: None,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn set_limit(resource: libc::__rlimit_resource_t, soft: u64, hard: u64) -> io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: soft as libc::rlim_t,
+        rlim_max: hard as libc::rlim_t,
+    };
+    if unsafe { libc::setrlimit(resource, &limit) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Waits for the process to exit, and returns its status and resource usage.
+#[cfg(target_os = "linux")]
+fn wait(pid: u32) -> io::Result<(libc::c_int, libc::rusage)> {
+    let mut status = 0;
+    let mut usage = unsafe { ::std::mem::zeroed() };
+    loop {
+        if unsafe { libc::wait4(pid as libc::pid_t, &mut status, 0, &mut usage) } != -1 {
+            return Ok((status, usage));
+        }
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn timeval_duration(time: libc::timeval) -> Duration {
+    Duration::new(time.tv_sec as u64, time.tv_usec as u32 * 1000)
+}
+
+/// Explains why a worker was killed by the given signal, using its stderr output to tell
+/// running out of memory from other crashes.
+#[cfg(target_os = "linux")]
+fn kill_reason(signal: libc::c_int, cpu_exceeded: bool, stderr: &str, limits: &Limits) -> String {
+    let cpu_limit = || format!("CPU time limit of {:?} exceeded", limits.cpu_time);
+    match signal {
+        libc::SIGXCPU => cpu_limit(),
+        libc::SIGKILL if cpu_exceeded => cpu_limit(),
+        _ if stderr.contains("memory allocation of") => {
+            format!("memory limit of {} exceeded", format_size(limits.memory))
+        }
+        _ if stderr.contains("overflowed its stack") => "stack overflow".to_owned(),
+        libc::SIGKILL => "killed by SIGKILL, possibly by the out of memory killer".to_owned(),
+        libc::SIGABRT => "aborted".to_owned(),
+        libc::SIGSEGV => "segmentation fault".to_owned(),
+        signal => format!("killed by signal {}", signal),
+    }
+}
+
+/// Parses a size in bytes such as `512M` or `2G`. The units are powers of 1024 and a number
+/// without a unit is in bytes.
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number = u64::from_str(number)
+        .map_err(|_| format!("Invalid size {:?}, expected for example 512M or 2G", s))?;
+    let multiplier: u64 = match unit.trim_end_matches("iB").trim_end_matches('B') {
+        "" => 1,
+        "K" | "k" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        unit => return Err(format!("Invalid unit {:?} in size {:?}, expected K, M or G", unit, s)),
+    };
+    match number.checked_mul(multiplier) {
+        Some(0) => Err(format!("Size {:?} must be larger than zero", s)),
+        Some(size) => Ok(size),
+        None => Err(format!("Size {:?} is too large", s)),
+    }
+}
+
+/// Formats a size in bytes in the largest unit it is at least one of.
+pub fn format_size(bytes: u64) -> String {
+    let units = [("GiB", 1 << 30), ("MiB", 1 << 20), ("KiB", 1 << 10)];
+    for &(unit, size) in &units {
+        if bytes >= size {
+            return format!("{:.1} {}", bytes as f64 / size as f64, unit);
+        }
+    }
+    format!("{} B", bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use allocations::AllocStats;
+    use base::{Error, ParseError, Part, Solution};
+    use std::time::Duration;
+    use super::{format_size, parse_size, Request, Response};
+
+    fn round_trip(response: Response) -> Response {
+        let mut data = vec![];
+        response.write_to(&mut data).unwrap();
+        Response::read_from(&data[..]).unwrap()
+    }
+
+    #[test]
+    fn request_round_trip() {
+        let request = Request {
+            day: 2,
+            part: Part::Two,
+            params: vec![("keypad".to_owned(), "cra=zy\n".to_owned())],
+            timeout: Some(Duration::from_millis(1500)),
+            count_allocations: true,
+            input: "ULL\nRRDDD\n".to_owned(),
+        };
+        let mut data = vec![];
+        request.write_to(&mut data).unwrap();
+        assert_eq!(request, Request::read_from(&data[..]).unwrap());
+    }
+
+    #[test]
+    fn request_without_optional_fields() {
+        let data = "aoc-sandbox 1\nday 1\npart 1\ninput 6\nR2, L3";
+        let request = Request::read_from(data.as_bytes()).unwrap();
+        assert_eq!(None, request.timeout);
+        assert!(!request.count_allocations);
+        assert!(request.params.is_empty());
+        assert_eq!("R2, L3", request.input);
+    }
+
+    #[test]
+    fn invalid_requests() {
+        assert!(Request::read_from(&b"aoc-sandbox 2\n"[..]).is_err());
+        assert!(Request::read_from(&b"aoc-sandbox 1\nday 1\ninput 3\nR2"[..]).is_err());
+        assert!(Request::read_from(&b"aoc-sandbox 1\nday 1\npart 1\ninput 9\nR2"[..]).is_err());
+        assert!(Request::read_from(&b"aoc-sandbox 1\nweek 1\n"[..]).is_err());
+    }
+
+    #[test]
+    fn response_round_trip() {
+        let response = round_trip(Response {
+            solution: Ok(Solution::Lines(vec!["#.".to_owned(), ".#".to_owned()])),
+            parse_time: Duration::from_micros(17),
+            solve_time: Duration::from_nanos(1),
+            parse_allocs: None,
+            solve_allocs: Some(AllocStats {
+                allocations: 3,
+                bytes: 300,
+                peak_bytes: 200,
+            }),
+        });
+        assert_eq!(Solution::Lines(vec!["#.".to_owned(), ".#".to_owned()]),
+                   response.solution.unwrap());
+        assert_eq!(Duration::from_micros(17), response.parse_time);
+        assert_eq!(Duration::from_nanos(1), response.solve_time);
+        assert_eq!(None, response.parse_allocs);
+        assert_eq!(Some(200), response.solve_allocs.map(|allocs| allocs.peak_bytes));
+    }
+
+    #[test]
+    fn answer_keeps_its_type() {
+        let solutions = vec![Solution::Text("1234".to_owned()),
+                             Solution::Text(String::new()),
+                             Solution::UInt(1234),
+                             Solution::Int(-5),
+                             Solution::Lines(vec!["12".to_owned(), "\\".to_owned()])];
+        for solution in solutions {
+            let response = round_trip(Response {
+                solution: Ok(solution.clone()),
+                parse_time: Duration::from_nanos(0),
+                solve_time: Duration::from_nanos(0),
+                parse_allocs: None,
+                solve_allocs: None,
+            });
+            assert_eq!(solution, response.solution.unwrap());
+        }
+    }
+
+    #[test]
+    fn error_round_trip() {
+        let errors = vec![Error::Parse(ParseError::new("Invalid direction: X")),
+                          Error::NoSolution("No crossing".to_owned()),
+                          Error::Panicked("index out of bounds".to_owned()),
+                          Error::TimedOut(Duration::from_secs(2)),
+                          Error::Killed("stack overflow".to_owned()),
+                          Error::Overflow("walking East 5 from Position(1, 0)".to_owned())];
+        for error in errors {
+            let response = round_trip(Response {
+                solution: Err(error.clone()),
+                parse_time: Duration::new(0, 0),
+                solve_time: Duration::new(0, 0),
+                parse_allocs: None,
+                solve_allocs: None,
+            });
+            let decoded = response.solution.unwrap_err();
+            assert_eq!(error.to_string(), decoded.to_string());
+            assert_eq!(::exit_code(&error), ::exit_code(&decoded));
+        }
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(Ok(512 << 20), parse_size("512M"));
+        assert_eq!(Ok(2 << 30), parse_size("2GiB"));
+        assert_eq!(Ok(100), parse_size("100"));
+        assert_eq!(Ok(4096), parse_size("4KB"));
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("1T").is_err());
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("99999999999G").is_err());
+    }
+
+    #[test]
+    fn size_formatting() {
+        assert_eq!("512 B", format_size(512));
+        assert_eq!("1.5 KiB", format_size(1536));
+        assert_eq!("2.0 GiB", format_size(2 << 30));
+    }
+}
@@ -0,0 +1,151 @@
+use base::Solution;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Clears the terminal and moves the cursor to the top left corner.
+pub static CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Polls files and directories for changes to their modification times.
+pub struct Watcher {
+    paths: Vec<PathBuf>,
+    interval: Duration,
+    state: BTreeMap<PathBuf, SystemTime>,
+}
+
+impl Watcher {
+    /// Starts watching the given paths. A directory is watched with everything in it, except
+    /// hidden entries and `target` directories, where build output goes.
+    pub fn new(paths: Vec<PathBuf>, interval: Duration) -> Self {
+        let state = snapshot(&paths);
+        Watcher {
+            paths,
+            interval,
+            state,
+        }
+    }
+
+    /// Returns the paths that were added, removed or modified since the last call.
+    pub fn changes(&mut self) -> BTreeSet<PathBuf> {
+        let state = snapshot(&self.paths);
+        let mut changed: BTreeSet<PathBuf> = self.state
+            .iter()
+            .filter(|&(path, time)| state.get(path) != Some(time))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.extend(state.keys().filter(|path| !self.state.contains_key(*path)).cloned());
+        self.state = state;
+        changed
+    }
+
+    /// Blocks until something changes, and then until nothing has changed for one polling
+    /// interval, so a file is not read while it is being written. Returns what changed.
+    pub fn wait(&mut self) -> BTreeSet<PathBuf> {
+        let mut changed = BTreeSet::new();
+        loop {
+            thread::sleep(self.interval);
+            let new_changes = self.changes();
+            if new_changes.is_empty() && !changed.is_empty() {
+                return changed;
+            }
+            changed.extend(new_changes);
+        }
+    }
+}
+
+/// Returns the modification time of every file in the given paths. Paths that can't be read
+/// are left out, so they count as changed once they can be read again.
+fn snapshot(paths: &[PathBuf]) -> BTreeMap<PathBuf, SystemTime> {
+    let mut state = BTreeMap::new();
+    for path in paths {
+        add_to_snapshot(path, &mut state);
+    }
+    state
+}
+
+fn add_to_snapshot(path: &Path, state: &mut BTreeMap<PathBuf, SystemTime>) {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return,
+    };
+    if !metadata.is_dir() {
+        if let Ok(time) = metadata.modified() {
+            state.insert(path.to_owned(), time);
+        }
+        return;
+    }
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') || name == "target" {
+            continue;
+        }
+        add_to_snapshot(&entry.path(), state);
+    }
+}
+
+/// Describes how a solution differs from the one found the previous time, to be shown after
+/// it. Empty if there is no previous solution to compare with.
+pub fn describe_change(previous: Option<&Solution>, current: &Solution) -> String {
+    match previous {
+        None => String::new(),
+        Some(previous) if previous == current => " (unchanged)".to_owned(),
+        Some(previous) => {
+            format!(" (changed, was {})", previous.to_string().replace('\n', "\\n"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base::Solution;
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use std::time::Duration;
+    use super::{describe_change, Watcher};
+
+    #[test]
+    fn change_description() {
+        let current = Solution::UInt(5);
+        assert_eq!("", describe_change(None, &current));
+        assert_eq!(" (unchanged)", describe_change(Some(&Solution::UInt(5)), &current));
+        assert_eq!(" (changed, was 3)", describe_change(Some(&Solution::UInt(3)), &current));
+        let lines = Solution::Lines(vec!["#.".to_owned(), ".#".to_owned()]);
+        assert_eq!(" (changed, was #.\\n.#)", describe_change(Some(&lines), &current));
+    }
+
+    #[test]
+    fn detects_changes_in_directory() {
+        let dir = env::temp_dir().join(format!("aoc-watch-test-{}", ::std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("target")).unwrap();
+        File::create(dir.join("a")).unwrap();
+
+        let mut watcher = Watcher::new(vec![dir.clone()], Duration::from_millis(10));
+        assert!(watcher.changes().is_empty());
+
+        File::create(dir.join("target").join("ignored")).unwrap();
+        assert!(watcher.changes().is_empty());
+
+        File::create(dir.join("b")).unwrap();
+        assert_eq!(vec![dir.join("b")], watcher.changes().into_iter().collect::<Vec<_>>());
+
+        fs::remove_file(dir.join("a")).unwrap();
+        assert_eq!(vec![dir.join("a")], watcher.changes().into_iter().collect::<Vec<_>>());
+
+        let mut f = fs::OpenOptions::new().append(true).open(dir.join("b")).unwrap();
+        f.write_all(b"x").unwrap();
+        f.set_modified(::std::time::SystemTime::now() + Duration::from_secs(1)).unwrap();
+        assert_eq!(vec![dir.join("b")], watcher.wait().into_iter().collect::<Vec<_>>());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,101 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Output};
+
+/// A day 1 input that crosses itself, so both parts have an answer.
+const DAY1_INPUT: &str = "R8, R4, R4, R8";
+
+/// Creates an empty directory for one test to run the CLI in.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("aoc-cli-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write_file(path: &Path, data: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, data).unwrap();
+}
+
+/// Runs the CLI in `dir` with the given arguments.
+fn aoc(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_aoc")).args(args).current_dir(dir).output().unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn record_and_verify_input_path_with_space() {
+    let dir = test_dir("space");
+    write_file(&dir.join("my inputs/1"), DAY1_INPUT);
+    let args = ["--day", "1", "--inputs-dir", "my inputs", "--answers", "ans"];
+    let output = aoc(&dir, &[&args[..], &["--record"]].concat());
+    assert!(output.status.success(), "{}", stderr(&output));
+    let output = aoc(&dir, &[&args[..], &["--verify"]].concat());
+    assert!(output.status.success(), "{}", stderr(&output));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Verification part 2: ok"), "{}", stdout);
+}
+
+#[test]
+fn all_with_part() {
+    let dir = test_dir("all-part");
+    write_file(&dir.join("inputs/1"), DAY1_INPUT);
+    let output = aoc(&dir, &["--all", "--part", "2", "--output", "csv"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let parts: Vec<&str> =
+        stdout.lines().skip(1).map(|line| line.split(',').nth(1).unwrap()).collect();
+    assert!(!parts.is_empty() && parts.iter().all(|&part| part == "2"), "{}", stdout);
+}
+
+#[test]
+fn unknown_day_without_input() {
+    let dir = test_dir("unknown-day");
+    for args in &[&["--day", "9"][..], &["--day", "9", "--bench"], &["--day", "9", "--watch"]] {
+        let output = aoc(&dir, args);
+        assert_eq!(Some(5), output.status.code(), "{:?}", args);
+        assert!(stderr(&output).contains("No solver for day 9, available days are 1, 2, 3"),
+                "{}",
+                stderr(&output));
+    }
+}
+
+#[test]
+fn verify_from_subdirectory() {
+    let dir = test_dir("subdir");
+    write_file(&dir.join("inputs/1"), DAY1_INPUT);
+    write_file(&dir.join("answers"), "1 1 inputs/1 uint 8\n1 2 inputs/1 uint 4\n");
+    write_file(&dir.join("aoc.toml"), "inputs_dir = \"inputs\"\nanswers = \"answers\"\n");
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    for run_dir in &[dir.clone(), dir.join("sub")] {
+        let output = aoc(run_dir, &["--day", "1", "--verify"]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    }
+    // Recording from the subdirectory keeps the keys relative to the answers file.
+    let output = aoc(&dir.join("sub"), &["--day", "1", "--record"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let answers = fs::read_to_string(dir.join("answers")).unwrap();
+    assert!(answers.contains("1 1 inputs/1 uint 8\n"), "{}", answers);
+}
+
+#[test]
+fn bench_goes_on_after_timeout() {
+    let dir = test_dir("bench-timeout");
+    // Long enough that parsing it takes more than the timeout.
+    write_file(&dir.join("inputs/1"), &["R1", "L1"].repeat(1_000_000).join(", "));
+    write_file(&dir.join("inputs/3"), "  5 10 25\n 3  4  5\n 6  8 10\n");
+    write_file(&dir.join("aoc.toml"),
+               "[bench]\nwarmup_ms = 1\nmeasurement_ms = 1\nsamples = 1\n");
+    let output = aoc(&dir, &["--all", "--bench", "--timeout", "0.005s", "--output", "csv"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let rows: Vec<Vec<&str>> = stdout.lines().map(|line| line.split(',').collect()).collect();
+    let error = rows[0].iter().position(|&field| field == "error").unwrap();
+    let errors: Vec<(&str, &str)> = rows[1..].iter().map(|row| (row[0], row[error])).collect();
+    assert_eq!(vec![("1", "Timed out after 5ms"); 3], errors[..3].to_vec(), "{}", stdout);
+    assert_eq!(vec![("3", ""); 3], errors[errors.len() - 3..].to_vec(), "{}", stdout);
+}
@@ -0,0 +1,264 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Everything that can go wrong when parsing input for or solving a problem.
+///
+/// Underlying errors are reference counted so the error can be cloned, for example to report
+/// the same parse error for both parts of a problem.
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// The problem input could not be parsed.
+    Parse(ParseError),
+    /// An argument, such as a part number, was not valid.
+    InvalidArgument(String),
+    /// The input is valid, but there is no solution to the problem for it.
+    NoSolution(String),
+    /// There is no solver for the requested day or part.
+    Unsupported(String),
+    /// Reading the input failed.
+    Io(Arc<io::Error>),
+    /// The solver panicked, with the given panic message.
+    Panicked(String),
+    /// The solver did not finish within the given time.
+    TimedOut(Duration),
+    /// The process running the solver was killed, for the given reason.
+    Killed(String),
+    /// A number in the solution became too large for its type while doing what is described.
+    Overflow(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Parse(ref e) => e.fmt(f),
+            Error::InvalidArgument(ref msg) |
+            Error::NoSolution(ref msg) |
+            Error::Unsupported(ref msg) => write!(f, "{}", msg),
+            Error::Io(ref e) => e.fmt(f),
+            Error::Panicked(ref msg) => write!(f, "Solver panicked: {}", msg),
+            Error::TimedOut(limit) => write!(f, "Timed out after {:?}", limit),
+            Error::Killed(ref reason) => write!(f, "Solver killed: {}", reason),
+            Error::Overflow(ref what) => write!(f, "Overflow when {}", what),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            Error::Parse(ref e) => e.source(),
+            Error::Io(ref e) => e.source(),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(Arc::new(e))
+    }
+}
+
+/// A range of bytes in the problem input, together with the line and column it starts at.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Span {
+    /// Byte offset of the first byte in the span.
+    pub start: usize,
+    /// Byte offset one past the last byte in the span.
+    pub end: usize,
+    /// The line the span starts on, counting from 1.
+    pub line: usize,
+    /// The column the span starts on, counting characters from 1.
+    pub column: usize,
+}
+
+impl Span {
+    /// Creates the span of the bytes `start..end` in `input`.
+    pub fn new(input: &str, start: usize, end: usize) -> Self {
+        let before = &input[..start];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Span {
+            start,
+            end,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// Creates the span of `part` in `input`. Panics if `part` is not a slice of `input`.
+    pub fn of(input: &str, part: &str) -> Self {
+        let start = (part.as_ptr() as usize).wrapping_sub(input.as_ptr() as usize);
+        assert!(start <= input.len() && start + part.len() <= input.len(),
+                "Span::of called with a part that is not a slice of the input");
+        Self::new(input, start, start + part.len())
+    }
+
+    /// Takes a span relative to some text that itself is found at `outer`, and makes it
+    /// relative to the text `outer` is relative to.
+    pub fn within(self, outer: Span) -> Self {
+        Span {
+            start: outer.start + self.start,
+            end: outer.start + self.end,
+            line: outer.line + self.line - 1,
+            column: if self.line == 1 { outer.column + self.column - 1 } else { self.column },
+        }
+    }
+}
+
+/// Describes why the problem input could not be parsed, and where in the input the problem is
+/// if that is known.
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    message: String,
+    source: Option<Arc<dyn StdError + Send + Sync>>,
+    span: Option<Span>,
+}
+
+impl ParseError {
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        ParseError {
+            message: message.into(),
+            source: None,
+            span: None,
+        }
+    }
+
+    /// Creates a parse error caused by another error, such as failing to parse a number.
+    pub fn with_source<S, E>(message: S, source: E) -> Self
+        where S: Into<String>,
+              E: StdError + Send + Sync + 'static
+    {
+        ParseError {
+            message: message.into(),
+            source: Some(Arc::new(source)),
+            span: None,
+        }
+    }
+
+    /// Sets where in the parsed text the error is.
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Makes the span of an error found while parsing `part`, a slice of `input`, relative to
+    /// `input`. An error without a span gets the span of all of `part`.
+    pub fn within(self, input: &str, part: &str) -> Self {
+        self.within_span(Span::of(input, part))
+    }
+
+    /// Makes the span of an error found while parsing the text at `outer` relative to the text
+    /// `outer` is relative to. An error without a span gets `outer` as its span.
+    pub fn within_span(mut self, outer: Span) -> Self {
+        self.span = Some(match self.span {
+            Some(span) => span.within(outer),
+            None => outer,
+        });
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "line {}, column {}: {}", span.line, span.column, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl StdError for ParseError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source.as_ref().map(|e| &**e as &(dyn StdError + 'static))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as StdError;
+    use std::io;
+    use std::str::FromStr;
+    use super::{Error, ParseError, Span};
+
+    #[test]
+    fn parse_error_source_chain() {
+        let cause = u32::from_str("x").unwrap_err();
+        let error = Error::from(ParseError::with_source("Invalid number: x", cause.clone()));
+        assert_eq!("Invalid number: x", error.to_string());
+        assert_eq!(cause.to_string(), error.source().unwrap().to_string());
+    }
+
+    #[test]
+    fn parse_error_without_source() {
+        let error = Error::from(ParseError::new("Invalid direction: P"));
+        assert_eq!("Invalid direction: P", error.to_string());
+        assert!(error.source().is_none());
+    }
+
+    #[test]
+    fn io_error_message() {
+        let error = Error::from(io::Error::new(io::ErrorKind::NotFound, "no such input"));
+        assert_eq!("no such input", error.to_string());
+    }
+
+    #[test]
+    fn span_new_line_and_column() {
+        let input = "R2, L3\nUDx\n";
+        assert_eq!(Span { start: 0, end: 2, line: 1, column: 1 }, Span::new(input, 0, 2));
+        assert_eq!(Span { start: 9, end: 10, line: 2, column: 3 }, Span::new(input, 9, 10));
+    }
+
+    #[test]
+    fn span_of_slice() {
+        let input = "ab\ncd ef";
+        assert_eq!(Span { start: 6, end: 8, line: 2, column: 4 }, Span::of(input, &input[6..]));
+    }
+
+    #[test]
+    fn error_within_part() {
+        let input = "R2, L3x, R1";
+        let part = &input[4..7];
+        let error = ParseError::new("Invalid distance: 3x").with_span(Span::of(part, &part[1..]));
+        let span = error.within(input, part).span().unwrap();
+        assert_eq!(Span { start: 5, end: 7, line: 1, column: 6 }, span);
+    }
+
+    #[test]
+    fn error_within_without_span() {
+        let input = "1 2\n3 x 4";
+        let error = ParseError::new("Invalid number: x").within(input, &input[6..7]);
+        assert_eq!("line 2, column 3: Invalid number: x", error.to_string());
+        assert_eq!("Invalid number: x", error.message());
+    }
+
+    #[test]
+    fn span_within_multiline() {
+        let outer = Span { start: 10, end: 20, line: 3, column: 5 };
+        let inner = Span { start: 4, end: 5, line: 2, column: 2 };
+        assert_eq!(Span { start: 14, end: 15, line: 4, column: 2 }, inner.within(outer));
+    }
+
+    #[test]
+    fn clone_keeps_source() {
+        let cause = u32::from_str("").unwrap_err();
+        let error = Error::from(ParseError::with_source("Invalid number", cause)).clone();
+        assert!(error.source().is_some());
+    }
+}
@@ -1,5 +1,17 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
 use std::str::FromStr;
 
+use error::{Error, ParseError, Span};
+
+mod coordinate;
+mod grid;
+mod path;
+mod segment;
+
+pub use self::coordinate::{Coordinate, Unsigned};
+pub use self::grid::{Grid, Neighbourhood};
+pub use self::path::{Path, SelfIntersections};
+pub use self::segment::Segment;
 
 #[derive(Eq, PartialEq, Debug, Copy, Clone)]
 pub enum Turn {
@@ -8,59 +20,210 @@ pub enum Turn {
 }
 
 impl FromStr for Turn {
-    type Err = String;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "R" => Ok(Turn::Right),
             "L" => Ok(Turn::Left),
-            _ => Err(format!("Invalid direction: {}", s)),
+            _ => Err(ParseError::new(format!("Invalid direction: {}", s))),
         }
     }
 }
 
-pub struct Step {
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub struct Step<T = i32> {
     turn: Turn,
-    distance: i32,
+    distance: T,
 }
 
-impl Step {
+impl<T: Coordinate> Step<T> {
     pub fn turn(&self) -> Turn {
         self.turn
     }
 
-    pub fn distance(&self) -> i32 {
+    pub fn distance(&self) -> T {
         self.distance
     }
 }
 
-impl FromStr for Step {
-    type Err = String;
+impl<T: Coordinate> FromStr for Step<T> {
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut chars = s.chars();
-        let turn_str = chars.next().ok_or("No direction at start".to_owned())?;
-        let turn = Turn::from_str(&turn_str.to_string())?;
-        let distance_str = chars.as_str();
-        let distance =
-            i32::from_str(distance_str).map_err(|_| format!("Invalid distance: {}", distance_str))?;
-        Ok(Step {
-            turn: turn,
-            distance: distance,
-        })
+        let turn_str = s.chars().next().ok_or_else(|| ParseError::new("No direction at start"))?;
+        let (turn_str, distance_str) = s.split_at(turn_str.len_utf8());
+        let turn = Turn::from_str(turn_str).map_err(|e| e.within(s, turn_str))?;
+        let distance = T::from_str(distance_str).map_err(|e| {
+                ParseError::with_source(format!("Invalid distance: {}", distance_str), e)
+                    .with_span(Span::of(s, distance_str))
+            })?;
+        Ok(Step { turn, distance })
     }
 }
 
+/// A position on a plane, with x growing to the east and y to the north. The coordinates are
+/// `i32` unless another `Coordinate` type is given.
 #[derive(Eq, PartialEq, Hash, Debug, Copy, Clone)]
-pub struct Position(pub i32, pub i32);
+pub struct Position<T = i32>(pub T, pub T);
+
+impl<T: Coordinate> Position<T> {
+    pub const ORIGO: Position<T> = Position(T::ZERO, T::ZERO);
+
+    /// Walks `distance` steps in `direction`. Panics on overflow in debug builds, like the
+    /// arithmetic operators.
+    pub fn walk(&mut self, direction: &Direction, distance: T) {
+        *self += direction.to_position_representation() * distance;
+    }
+
+    /// Walks `distance` steps in `direction`, or fails without moving if a coordinate would
+    /// overflow.
+    pub fn checked_walk(&mut self, direction: &Direction, distance: T) -> Result<(), Error> {
+        let overflow = || {
+            Error::Overflow(format!("walking {:?} {} from {:?}", direction, distance, self))
+        };
+        let offset = direction.to_position_representation::<T>()
+            .checked_mul(distance)
+            .ok_or_else(overflow)?;
+        *self = self.checked_add(offset).ok_or_else(overflow)?;
+        Ok(())
+    }
 
-impl Position {
-    pub fn walk(&mut self, direction: &Direction, distance: i32) {
-        let vector = direction.to_position_representation();
-        self.0 += vector.0 * distance as i32;
-        self.1 += vector.1 * distance as i32;
+    /// The manhattan distance from origo. Panics on overflow, which can only happen when both
+    /// coordinates are close to `T::MIN`.
+    pub fn distance_from_origo(&self) -> T::Distance {
+        self.manhattan_distance(Position::ORIGO)
     }
 
-    pub fn distance_from_origo(&self) -> u32 {
-        (self.0.abs() + self.1.abs()) as u32
+    pub fn checked_distance_from_origo(&self) -> Option<T::Distance> {
+        self.checked_manhattan_distance(Position::ORIGO)
+    }
+
+    /// The distance when only moving along the axes, as a taxicab does. Panics on overflow.
+    pub fn manhattan_distance(&self, other: Position<T>) -> T::Distance {
+        self.checked_manhattan_distance(other)
+            .unwrap_or_else(|| panic!("Distance from {:?} to {:?} overflows", self, other))
+    }
+
+    pub fn checked_manhattan_distance(&self, other: Position<T>) -> Option<T::Distance> {
+        self.0.abs_diff(other.0).checked_add(self.1.abs_diff(other.1))
+    }
+
+    /// The distance when also moving diagonally, as a king in chess does.
+    pub fn chebyshev_distance(&self, other: Position<T>) -> T::Distance {
+        self.0.abs_diff(other.0).max(self.1.abs_diff(other.1))
+    }
+
+    /// The square of the straight line distance. Exact, unlike the distance itself. Panics on
+    /// overflow.
+    pub fn squared_euclidean_distance(&self, other: Position<T>) -> T::Distance {
+        self.checked_squared_euclidean_distance(other)
+            .unwrap_or_else(|| panic!("Distance from {:?} to {:?} overflows", self, other))
+    }
+
+    pub fn checked_squared_euclidean_distance(&self, other: Position<T>) -> Option<T::Distance> {
+        let dx = self.0.abs_diff(other.0);
+        let dy = self.1.abs_diff(other.1);
+        dx.checked_mul(dx)?.checked_add(dy.checked_mul(dy)?)
+    }
+
+    pub fn checked_add(&self, other: Position<T>) -> Option<Position<T>> {
+        Some(Position(self.0.checked_add(other.0)?, self.1.checked_add(other.1)?))
+    }
+
+    pub fn checked_sub(&self, other: Position<T>) -> Option<Position<T>> {
+        Some(Position(self.0.checked_sub(other.0)?, self.1.checked_sub(other.1)?))
+    }
+
+    pub fn checked_mul(&self, factor: T) -> Option<Position<T>> {
+        Some(Position(self.0.checked_mul(factor)?, self.1.checked_mul(factor)?))
+    }
+
+    /// Rotates the position a quarter turn around origo, clockwise for a right turn.
+    pub fn rotate(&self, turn: &Turn) -> Position<T> {
+        match *turn {
+            Turn::Right => Position(self.1, -self.0),
+            Turn::Left => Position(-self.1, self.0),
+        }
+    }
+
+    /// Rotates the position a quarter turn around `pivot`, clockwise for a right turn.
+    pub fn rotate_about(&self, pivot: Position<T>, turn: &Turn) -> Position<T> {
+        (*self - pivot).rotate(turn) + pivot
+    }
+
+    /// Mirrors the position in the y axis, negating x.
+    pub fn mirror_x(&self) -> Position<T> {
+        Position(-self.0, self.1)
+    }
+
+    /// Mirrors the position in the x axis, negating y.
+    pub fn mirror_y(&self) -> Position<T> {
+        Position(self.0, -self.1)
+    }
+
+    /// Mirrors the position in the line `x = y`, swapping the coordinates.
+    pub fn transpose(&self) -> Position<T> {
+        Position(self.1, self.0)
+    }
+}
+
+impl<T: Coordinate> Add for Position<T> {
+    type Output = Position<T>;
+    fn add(self, other: Position<T>) -> Position<T> {
+        Position(self.0 + other.0, self.1 + other.1)
+    }
+}
+
+impl<T: Coordinate> AddAssign for Position<T> {
+    fn add_assign(&mut self, other: Position<T>) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Coordinate> Sub for Position<T> {
+    type Output = Position<T>;
+    fn sub(self, other: Position<T>) -> Position<T> {
+        Position(self.0 - other.0, self.1 - other.1)
+    }
+}
+
+impl<T: Coordinate> SubAssign for Position<T> {
+    fn sub_assign(&mut self, other: Position<T>) {
+        *self = *self - other;
+    }
+}
+
+impl<T: Coordinate> Neg for Position<T> {
+    type Output = Position<T>;
+    fn neg(self) -> Position<T> {
+        Position(-self.0, -self.1)
+    }
+}
+
+/// Scales both coordinates.
+impl<T: Coordinate> Mul<T> for Position<T> {
+    type Output = Position<T>;
+    fn mul(self, factor: T) -> Position<T> {
+        Position(self.0 * factor, self.1 * factor)
+    }
+}
+
+/// Parses a position written as `x,y`.
+impl<T: Coordinate> FromStr for Position<T> {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut coordinates = s.splitn(2, ',');
+        let mut coordinate = |name| {
+            let coordinate_str = coordinates.next()
+                .ok_or_else(|| ParseError::new(format!("Missing {} coordinate", name)))?;
+            T::from_str(coordinate_str.trim()).map_err(|e| {
+                ParseError::with_source(format!("Invalid {} coordinate: {}", name, coordinate_str),
+                                        e)
+                    .with_span(Span::of(s, coordinate_str))
+            })
+        };
+        let x = coordinate("x")?;
+        let y = coordinate("y")?;
+        Ok(Position(x, y))
     }
 }
 
@@ -80,12 +243,13 @@ impl Direction {
         }
     }
 
-    pub fn to_position_representation(&self) -> Position {
+    /// The position one step in this direction from origo.
+    pub fn to_position_representation<T: Coordinate>(&self) -> Position<T> {
         match *self {
-            Direction::North => Position(0, 1),
-            Direction::East => Position(1, 0),
-            Direction::South => Position(0, -1),
-            Direction::West => Position(-1, 0),
+            Direction::North => Position(T::ZERO, T::ONE),
+            Direction::East => Position(T::ONE, T::ZERO),
+            Direction::South => Position(T::ZERO, -T::ONE),
+            Direction::West => Position(-T::ONE, T::ZERO),
         }
     }
 
@@ -109,16 +273,18 @@ impl Direction {
 }
 
 impl FromStr for Direction {
-    type Err = String;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut chars = s.chars();
-        let first_char = chars.next().ok_or("No direction at start".to_owned())?;
+        let first_char = s.chars().next().ok_or_else(|| ParseError::new("No direction at start"))?;
         match first_char {
             'N'|'U' => Ok(Direction::North),
             'E'|'R' => Ok(Direction::East),
             'S'|'D' => Ok(Direction::South),
             'W'|'L' => Ok(Direction::West),
-            _ => Err(format!("Invalid direction: {}", first_char)),
+            _ => {
+                let span = Span::new(s, 0, first_char.len_utf8());
+                Err(ParseError::new(format!("Invalid direction: {}", first_char)).with_span(span))
+            }
         }
     }
 }
@@ -126,32 +292,49 @@ impl FromStr for Direction {
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
+    use error::Error;
     use super::{Turn, Step, Direction, Position};
 
     #[test]
     fn step_from_str_r() {
-        let step = Step::from_str("R1").unwrap();
+        let step = Step::<i32>::from_str("R1").unwrap();
         assert_eq!(Turn::Right, step.turn);
         assert_eq!(1, step.distance);
     }
 
     #[test]
     fn step_from_str_l() {
-        let step = Step::from_str("L99").unwrap();
+        let step = Step::<i32>::from_str("L99").unwrap();
         assert_eq!(Turn::Left, step.turn);
         assert_eq!(99, step.distance);
     }
 
     #[test]
     fn step_from_str_negative() {
-        let step = Step::from_str("L-15").unwrap();
+        let step = Step::<i32>::from_str("L-15").unwrap();
         assert_eq!(Turn::Left, step.turn);
         assert_eq!(-15, step.distance);
     }
 
     #[test]
     fn step_from_str_invalid() {
-        assert!(Step::from_str("P87").is_err());
+        assert!(Step::<i32>::from_str("P87").is_err());
+    }
+
+    #[test]
+    fn step_from_str_invalid_distance_has_source() {
+        use std::error::Error;
+        let error = Step::<i32>::from_str("R1x").unwrap_err();
+        assert_eq!("Invalid distance: 1x", error.message());
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn step_from_str_error_spans() {
+        let span = Step::<i32>::from_str("R1x").unwrap_err().span().unwrap();
+        assert_eq!((1, 3, 2), (span.start, span.end, span.column));
+        let span = Step::<i32>::from_str("P87").unwrap_err().span().unwrap();
+        assert_eq!((0, 1, 1), (span.start, span.end, span.column));
     }
 
     #[test]
@@ -168,6 +351,190 @@ mod tests {
         assert_eq!(Position(99, -1), position);
     }
 
+    #[test]
+    fn position_addition() {
+        let (a, b, c) = (Position(3, -7), Position(-2, 5), Position(10, 1));
+        assert_eq!(Position(1, -2), a + b);
+        assert_eq!(a + b, b + a);
+        assert_eq!((a + b) + c, a + (b + c));
+        assert_eq!(a, a + Position::ORIGO);
+        assert_eq!(Position::ORIGO, a + -a);
+        assert_eq!(a - b, a + -b);
+        assert_eq!(a, (a - b) + b);
+        assert_eq!(a, -(-a));
+        let mut d = a;
+        d += b;
+        d -= b;
+        assert_eq!(a, d);
+    }
+
+    #[test]
+    fn position_scaling() {
+        let (a, b) = (Position(3, -7), Position(-2, 5));
+        assert_eq!(Position(-9, 21), a * -3);
+        assert_eq!(a * 4 + b * 4, (a + b) * 4);
+        assert_eq!(a * 2 + a * 5, a * 7);
+        assert_eq!(-a, a * -1);
+    }
+
+    #[test]
+    fn position_walk_is_addition() {
+        let mut position = Position(4, 4);
+        position.walk(&Direction::West, 6);
+        assert_eq!(Position(4, 4) + Direction::West.to_position_representation() * 6, position);
+    }
+
+    #[test]
+    fn position_distances() {
+        let (a, b): (Position, Position) = (Position(1, -2), Position(-3, 5));
+        assert_eq!(11, a.manhattan_distance(b));
+        assert_eq!(7, a.chebyshev_distance(b));
+        assert_eq!(65, a.squared_euclidean_distance(b));
+        assert_eq!(0, a.manhattan_distance(a));
+        assert_eq!(a.distance_from_origo(), a.manhattan_distance(Position::ORIGO));
+    }
+
+    #[test]
+    fn position_distance_identities() {
+        let positions: [Position; 4] =
+            [Position(0, 0), Position(1, -2), Position(-3, 5), Position(7, 7)];
+        for &a in &positions {
+            for &b in &positions {
+                assert_eq!(a.manhattan_distance(b), b.manhattan_distance(a));
+                assert_eq!(a.chebyshev_distance(b), b.chebyshev_distance(a));
+                assert_eq!(a.squared_euclidean_distance(b), b.squared_euclidean_distance(a));
+                // The distance between two positions is the distance of their difference.
+                assert_eq!(a.manhattan_distance(b), (a - b).distance_from_origo());
+                assert!(a.chebyshev_distance(b) <= a.manhattan_distance(b));
+                assert!(a.manhattan_distance(b) <= 2 * a.chebyshev_distance(b));
+                for &c in &positions {
+                    assert!(a.manhattan_distance(c) <=
+                            a.manhattan_distance(b) + b.manhattan_distance(c));
+                    assert!(a.chebyshev_distance(c) <=
+                            a.chebyshev_distance(b) + b.chebyshev_distance(c));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn position_rotation() {
+        let a = Position(2, 5);
+        assert_eq!(Position(5, -2), a.rotate(&Turn::Right));
+        assert_eq!(Position(-5, 2), a.rotate(&Turn::Left));
+        assert_eq!(a, a.rotate(&Turn::Right).rotate(&Turn::Left));
+        assert_eq!(-a, a.rotate(&Turn::Right).rotate(&Turn::Right));
+        assert_eq!(a.rotate(&Turn::Left),
+                   a.rotate(&Turn::Right).rotate(&Turn::Right).rotate(&Turn::Right));
+    }
+
+    #[test]
+    fn position_rotation_follows_direction() {
+        for &direction in &[Direction::North, Direction::East, Direction::South, Direction::West] {
+            for turn in &[Turn::Right, Turn::Left] {
+                let vector: Position = direction.to_position_representation();
+                assert_eq!(direction.turn(turn).to_position_representation(), vector.rotate(turn));
+            }
+        }
+    }
+
+    #[test]
+    fn position_rotation_identities() {
+        let (a, b, pivot) = (Position(2, 5), Position(-4, 1), Position(-1, 3));
+        for turn in &[Turn::Right, Turn::Left] {
+            assert_eq!((a + b).rotate(turn), a.rotate(turn) + b.rotate(turn));
+            assert_eq!((a * 3).rotate(turn), a.rotate(turn) * 3);
+            assert_eq!(a.distance_from_origo(), a.rotate(turn).distance_from_origo());
+            assert_eq!(a.squared_euclidean_distance(b),
+                       a.rotate(turn).squared_euclidean_distance(b.rotate(turn)));
+            assert_eq!(pivot, pivot.rotate_about(pivot, turn));
+            assert_eq!(a.manhattan_distance(pivot),
+                       a.rotate_about(pivot, turn).manhattan_distance(pivot));
+            assert_eq!(a.rotate(turn), a.rotate_about(Position::ORIGO, turn));
+        }
+        assert_eq!(Position(1, 0), a.rotate_about(pivot, &Turn::Right));
+    }
+
+    #[test]
+    fn position_reflection() {
+        let (a, b) = (Position(2, -5), Position(-4, 1));
+        assert_eq!(Position(-2, -5), a.mirror_x());
+        assert_eq!(Position(2, 5), a.mirror_y());
+        assert_eq!(Position(-5, 2), a.transpose());
+        assert_eq!(a, a.mirror_x().mirror_x());
+        assert_eq!(a, a.mirror_y().mirror_y());
+        assert_eq!(a, a.transpose().transpose());
+        assert_eq!(-a, a.mirror_x().mirror_y());
+        assert_eq!(a.mirror_x() + b.mirror_x(), (a + b).mirror_x());
+        assert_eq!(a.squared_euclidean_distance(b),
+                   a.transpose().squared_euclidean_distance(b.transpose()));
+        // Two reflections in lines at 45 degrees make a quarter turn.
+        assert_eq!(a.rotate(&Turn::Left), a.transpose().mirror_x());
+    }
+
+    #[test]
+    fn wide_coordinates() {
+        let mut position: Position<i64> = Position(0, 0);
+        position.walk(&Direction::East, 3_000_000_000);
+        position.walk(&Direction::South, 5_000_000_000);
+        assert_eq!(Position(3_000_000_000, -5_000_000_000), position);
+        assert_eq!(8_000_000_000, position.distance_from_origo());
+
+        let step = Step::<i128>::from_str("L-100000000000000000000000").unwrap();
+        assert_eq!(-100_000_000_000_000_000_000_000, step.distance());
+        let position = Position::<i128>::from_str("170141183460469231731687303715884105727,0");
+        assert_eq!(Position(i128::MAX, 0), position.unwrap());
+        assert!(Position::<i64>::from_str("9223372036854775808,0").is_err());
+    }
+
+    #[test]
+    fn checked_walk() {
+        let mut position = Position(5, 5);
+        position.checked_walk(&Direction::West, -10).unwrap();
+        assert_eq!(Position(15, 5), position);
+
+        let mut position = Position(1, 0);
+        let error = position.checked_walk(&Direction::East, i32::MAX).unwrap_err();
+        assert!(matches!(error, Error::Overflow(_)));
+        assert_eq!("Overflow when walking East 2147483647 from Position(1, 0)",
+                   error.to_string());
+        assert_eq!(Position(1, 0), position);
+
+        // Walking backwards from the smallest distance overflows before adding.
+        let mut position: Position<i64> = Position(0, 0);
+        assert!(position.checked_walk(&Direction::North, i64::MIN).is_ok());
+        assert!(position.checked_walk(&Direction::South, i64::MIN).is_err());
+
+        let mut position: Position<i128> = Position(i128::MAX - 1, 0);
+        position.checked_walk(&Direction::East, 1).unwrap();
+        assert!(position.checked_walk(&Direction::East, 1).is_err());
+    }
+
+    #[test]
+    fn checked_distances() {
+        let far = Position(i32::MIN, i32::MIN);
+        assert_eq!(None, far.checked_manhattan_distance(Position::ORIGO));
+        assert_eq!(Some(u32::MAX), far.checked_manhattan_distance(Position(0, -1)));
+        assert_eq!(u32::MAX, Position(i32::MIN, 0).chebyshev_distance(Position(i32::MAX, 0)));
+        assert_eq!(None, far.checked_squared_euclidean_distance(Position::ORIGO));
+        let far: Position<i64> = Position(i64::from(i32::MIN), i64::from(i32::MIN));
+        assert_eq!(Some(1 << 32), far.checked_manhattan_distance(Position::ORIGO));
+    }
+
+    #[test]
+    fn position_from_str() {
+        assert_eq!(Position(3, -14), Position::from_str("3,-14").unwrap());
+        assert_eq!(Position(0, 7), Position::from_str(" 0 , 7 ").unwrap());
+    }
+
+    #[test]
+    fn position_from_str_invalid() {
+        assert!(Position::<i32>::from_str("3").is_err());
+        assert!(Position::<i32>::from_str("3,").is_err());
+        assert!(Position::<i32>::from_str("a,1").is_err());
+        assert!(Position::<i32>::from_str("1,2,3").is_err());
+    }
+
     #[test]
     fn direction_from_str_north() {
         let direction = Direction::from_str("N").unwrap();
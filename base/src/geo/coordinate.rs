@@ -0,0 +1,79 @@
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::num::ParseIntError;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// A signed integer type positions can use for their coordinates.
+pub trait Coordinate: Copy + Ord + Hash + Debug + Display + Default + Send + Sync + 'static +
+                      FromStr<Err = ParseIntError> + Add<Output = Self> + AddAssign +
+                      Sub<Output = Self> + SubAssign + Mul<Output = Self> + Neg<Output = Self>
+{
+    /// The unsigned type of the same width, which distances between positions are measured in.
+    type Distance: Unsigned;
+
+    const ZERO: Self;
+    const ONE: Self;
+    const MIN: Self;
+    const MAX: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn checked_neg(self) -> Option<Self>;
+    /// The absolute difference, which never overflows.
+    fn abs_diff(self, other: Self) -> Self::Distance;
+}
+
+/// An unsigned integer type distances are measured in.
+pub trait Unsigned: Copy + Ord + Hash + Debug + Display + Default + Send + Sync + 'static +
+                    Add<Output = Self> + Mul<Output = Self>
+{
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_coordinate {
+    ($($signed:ident => $unsigned:ident),*) => {$(
+        impl Coordinate for $signed {
+            type Distance = $unsigned;
+
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const MIN: Self = $signed::MIN;
+            const MAX: Self = $signed::MAX;
+
+            fn checked_add(self, other: Self) -> Option<Self> {
+                $signed::checked_add(self, other)
+            }
+
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                $signed::checked_sub(self, other)
+            }
+
+            fn checked_mul(self, other: Self) -> Option<Self> {
+                $signed::checked_mul(self, other)
+            }
+
+            fn checked_neg(self) -> Option<Self> {
+                $signed::checked_neg(self)
+            }
+
+            fn abs_diff(self, other: Self) -> $unsigned {
+                $signed::abs_diff(self, other)
+            }
+        }
+
+        impl Unsigned for $unsigned {
+            fn checked_add(self, other: Self) -> Option<Self> {
+                $unsigned::checked_add(self, other)
+            }
+
+            fn checked_mul(self, other: Self) -> Option<Self> {
+                $unsigned::checked_mul(self, other)
+            }
+        }
+    )*}
+}
+
+impl_coordinate!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
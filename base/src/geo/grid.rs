@@ -0,0 +1,338 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use error::{ParseError, Span};
+use geo::Position;
+
+/// Which cells around a cell count as its neighbours.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Neighbourhood {
+    /// The four cells sharing a side with the cell.
+    Four,
+    /// The eight cells sharing a side or a corner with the cell.
+    Eight,
+}
+
+impl Neighbourhood {
+    fn offsets(&self) -> &'static [Position] {
+        static FOUR: [Position; 4] =
+            [Position(0, 1), Position(1, 0), Position(0, -1), Position(-1, 0)];
+        static EIGHT: [Position; 8] = [Position(0, 1),
+                                       Position(1, 1),
+                                       Position(1, 0),
+                                       Position(1, -1),
+                                       Position(0, -1),
+                                       Position(-1, -1),
+                                       Position(-1, 0),
+                                       Position(-1, 1)];
+        match *self {
+            Neighbourhood::Four => &FOUR,
+            Neighbourhood::Eight => &EIGHT,
+        }
+    }
+}
+
+/// A rectangular grid of cells addressed by `Position`, with `Position(0, 0)` in the south west
+/// corner. Like for `Direction`, x grows to the east and y to the north. So when the grid is
+/// read from or written as text, the first line is the northmost row, the one with the largest
+/// y.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    /// The cells row by row, from north to south.
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid with every cell set to `value`.
+    pub fn new(width: usize, height: usize, value: T) -> Self
+        where T: Clone
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Creates a grid with every cell set to what `f` returns for its position.
+    pub fn from_fn<F: FnMut(Position) -> T>(width: usize, height: usize, mut f: F) -> Self {
+        let mut grid = Grid {
+            width,
+            height,
+            cells: Vec::with_capacity(width * height),
+        };
+        for i in 0..width * height {
+            let position = grid.position_of(i);
+            grid.cells.push(f(position));
+        }
+        grid
+    }
+
+    /// Parses a grid from text with one line per row, turning every character into a cell with
+    /// `f`. Fails if `f` returns `None` for a character or if the lines are not all the same
+    /// length. Text with only empty lines gives an empty grid.
+    pub fn parse<F: FnMut(char) -> Option<T>>(text: &str, mut f: F) -> Result<Self, ParseError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = vec![];
+        for line in text.lines() {
+            let mut line_width = 0;
+            for (i, c) in line.char_indices() {
+                let cell = f(c).ok_or_else(|| {
+                        ParseError::new(format!("Invalid cell: {}", c))
+                            .with_span(Span::of(text, &line[i..i + c.len_utf8()]))
+                    })?;
+                cells.push(cell);
+                line_width += 1;
+            }
+            match width {
+                None => width = Some(line_width),
+                Some(width) if width != line_width => {
+                    let message = format!("Row has {} cells, expected {}", line_width, width);
+                    return Err(ParseError::new(message).with_span(Span::of(text, line)));
+                }
+                Some(_) => (),
+            }
+            height += 1;
+        }
+        let width = width.unwrap_or(0);
+        Ok(Grid {
+            width,
+            // Rows without cells don't make the grid any taller.
+            height: if width == 0 { 0 } else { height },
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns whether the position is inside the grid.
+    pub fn contains(&self, position: Position) -> bool {
+        self.index_of(position).is_some()
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.index_of(position).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        self.index_of(position).map(move |i| &mut self.cells[i])
+    }
+
+    /// Sets the cell at the position and returns its old value. Panics if the position is
+    /// outside the grid.
+    pub fn set(&mut self, position: Position, value: T) -> T {
+        ::std::mem::replace(&mut self[position], value)
+    }
+
+    /// Iterates over the rows from north to south, each from west to east.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on zero, and a grid without columns has no cells anyway.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Iterates over every cell and its position, in the same order as `rows`.
+    pub fn cells(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.cells.iter().enumerate().map(move |(i, cell)| (self.position_of(i), cell))
+    }
+
+    /// Iterates over the neighbours of the position that are inside the grid, clockwise from
+    /// north. Neighbours whose coordinates would overflow are outside the grid too.
+    pub fn neighbours(&self,
+                      position: Position,
+                      neighbourhood: Neighbourhood)
+                      -> impl Iterator<Item = (Position, &T)> {
+        neighbourhood.offsets().iter().filter_map(move |offset| {
+            let neighbour = position.checked_add(*offset)?;
+            self.get(neighbour).map(|cell| (neighbour, cell))
+        })
+    }
+
+    /// Returns the position of the first cell, in the order of `cells`, the predicate is true
+    /// for.
+    pub fn find<P: FnMut(&T) -> bool>(&self, predicate: P) -> Option<Position> {
+        self.cells.iter().position(predicate).map(|i| self.position_of(i))
+    }
+
+    /// Creates a grid of the same size with `f` applied to every cell.
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Renders the grid as text with one line per row, turning every cell into a character
+    /// with `f`. The opposite of `parse`.
+    pub fn render<F: FnMut(&T) -> char>(&self, mut f: F) -> String {
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+        for (i, row) in self.rows().enumerate() {
+            if i > 0 {
+                text.push('\n');
+            }
+            text.extend(row.iter().map(&mut f));
+        }
+        text
+    }
+
+    fn index_of(&self, position: Position) -> Option<usize> {
+        let Position(x, y) = position;
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return None;
+        }
+        Some((self.height - 1 - y as usize) * self.width + x as usize)
+    }
+
+    fn position_of(&self, index: usize) -> Position {
+        let x = index % self.width;
+        let y = self.height - 1 - index / self.width;
+        Position(x as i32, y as i32)
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        match self.index_of(position) {
+            Some(i) => &self.cells[i],
+            None => panic!("{:?} is outside the {}x{} grid", position, self.width, self.height),
+        }
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        match self.index_of(position) {
+            Some(i) => &mut self.cells[i],
+            None => panic!("{:?} is outside the {}x{} grid", position, self.width, self.height),
+        }
+    }
+}
+
+/// Writes every cell with its own `Display`, one line per row from north to south.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, row) in self.rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::Position;
+    use super::{Grid, Neighbourhood};
+
+    fn digits(text: &str) -> Grid<u32> {
+        Grid::parse(text, |c| c.to_digit(10)).unwrap()
+    }
+
+    #[test]
+    fn parse_and_get() {
+        let grid = digits("123\n456\n");
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!(Some(&4), grid.get(Position(0, 0)));
+        assert_eq!(Some(&3), grid.get(Position(2, 1)));
+        assert_eq!(None, grid.get(Position(3, 0)));
+        assert_eq!(None, grid.get(Position(0, -1)));
+        assert_eq!(6, grid[Position(2, 0)]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = Grid::parse("12\n3x", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!("Invalid cell: x", error.message());
+        assert_eq!((2, 2), (error.span().unwrap().line, error.span().unwrap().column));
+
+        let error = Grid::parse("12\n345\n", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!("Row has 3 cells, expected 2", error.message());
+        assert_eq!(2, error.span().unwrap().line);
+    }
+
+    #[test]
+    fn parse_empty() {
+        let grid = digits("");
+        assert_eq!((0, 0), (grid.width(), grid.height()));
+        assert_eq!(0, grid.cells().count());
+        assert_eq!("", grid.to_string());
+        let grid = digits("\n\n\n");
+        assert_eq!((0, 0), (grid.width(), grid.height()));
+        assert_eq!(None, grid.get(Position(0, 0)));
+        assert_eq!(0, grid.rows().count());
+    }
+
+    #[test]
+    fn set_and_display() {
+        let mut grid = digits("12\n34");
+        assert_eq!(3, grid.set(Position(0, 0), 9));
+        *grid.get_mut(Position(1, 1)).unwrap() = 0;
+        assert_eq!("10\n94", grid.to_string());
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_outside() {
+        Grid::new(2, 2, 0).set(Position(2, 0), 1);
+    }
+
+    #[test]
+    fn render_round_trip() {
+        let text = "#..\n.#.\n..#";
+        let grid = Grid::parse(text, |c| Some(c == '#')).unwrap();
+        assert_eq!(text, grid.render(|&lit| if lit { '#' } else { '.' }));
+    }
+
+    #[test]
+    fn rows_and_cells() {
+        let grid = digits("12\n34\n56");
+        let rows: Vec<&[u32]> = grid.rows().collect();
+        assert_eq!(vec![&[1, 2][..], &[3, 4], &[5, 6]], rows);
+        let cells: Vec<(Position, u32)> = grid.cells().map(|(p, &c)| (p, c)).collect();
+        assert_eq!((Position(0, 2), 1), cells[0]);
+        assert_eq!((Position(1, 0), 6), cells[5]);
+    }
+
+    #[test]
+    fn from_fn_and_map() {
+        let grid = Grid::from_fn(3, 2, |Position(x, y)| x * 10 + y);
+        assert_eq!(21, grid[Position(2, 1)]);
+        assert_eq!("112131\n102030", grid.map(|n| n + 10).to_string());
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = digits("123\n456\n789");
+        let around = |position, neighbourhood| -> Vec<u32> {
+            grid.neighbours(position, neighbourhood).map(|(_, &c)| c).collect()
+        };
+        assert_eq!(vec![2, 6, 8, 4], around(Position(1, 1), Neighbourhood::Four));
+        assert_eq!(vec![2, 3, 6, 9, 8, 7, 4, 1], around(Position(1, 1), Neighbourhood::Eight));
+        assert_eq!(vec![2, 4], around(Position(0, 2), Neighbourhood::Four));
+        assert_eq!(vec![2, 5, 4], around(Position(0, 2), Neighbourhood::Eight));
+        assert!(around(Position(i32::MAX, i32::MAX), Neighbourhood::Eight).is_empty());
+        assert!(around(Position(i32::MIN, i32::MIN), Neighbourhood::Eight).is_empty());
+    }
+
+    #[test]
+    fn find() {
+        let grid = digits("123\n456");
+        assert_eq!(Some(Position(1, 0)), grid.find(|&c| c == 5));
+        assert_eq!(None, grid.find(|&c| c == 7));
+    }
+}
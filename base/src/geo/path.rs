@@ -0,0 +1,304 @@
+use error::Error;
+use geo::{Coordinate, Direction, Position, Segment, Step, Unsigned};
+
+/// The positions visited when walking a sequence of steps, turning before each step.
+#[derive(Eq, PartialEq, Hash, Debug, Clone)]
+pub struct Path<T = i32> {
+    /// The start and the position after every step.
+    vertices: Vec<Position<T>>,
+}
+
+impl<T: Coordinate> Path<T> {
+    /// Walks the steps from `start`, facing `heading` before the first turn. Fails if a
+    /// position on the way does not fit in `T`.
+    pub fn new(start: Position<T>, heading: Direction, steps: &[Step<T>]) -> Result<Self, Error> {
+        let mut vertices = Vec::with_capacity(steps.len() + 1);
+        vertices.push(start);
+        let (mut position, mut direction) = (start, heading);
+        for step in steps {
+            direction = direction.turn(&step.turn());
+            position.checked_walk(&direction, step.distance())?;
+            vertices.push(position);
+        }
+        Ok(Path { vertices })
+    }
+
+    /// The start followed by the position after every step.
+    pub fn vertices(&self) -> &[Position<T>] {
+        &self.vertices
+    }
+
+    /// The straight line walked by every step, in order.
+    pub fn segments<'a>(&'a self) -> impl Iterator<Item = Segment<T>> + 'a {
+        self.vertices.windows(2).map(|pair| segment(pair[0], pair[1]))
+    }
+
+    pub fn start(&self) -> Position<T> {
+        self.vertices[0]
+    }
+
+    pub fn endpoint(&self) -> Position<T> {
+        self.vertices[self.vertices.len() - 1]
+    }
+
+    /// The number of unit steps walked in total. Panics on overflow.
+    pub fn length(&self) -> T::Distance {
+        self.checked_length().expect("The path length overflows")
+    }
+
+    pub fn checked_length(&self) -> Option<T::Distance> {
+        self.segments().try_fold(T::Distance::default(), |sum, s| sum.checked_add(s.length()))
+    }
+
+    /// The south west and north east corners of the smallest box containing the path.
+    pub fn bounding_box(&self) -> (Position<T>, Position<T>) {
+        let start = self.start();
+        self.vertices.iter().fold((start, start), |(min, max), p| {
+            (Position(min.0.min(p.0), min.1.min(p.1)), Position(max.0.max(p.0), max.1.max(p.1)))
+        })
+    }
+
+    /// The first position on the path with the greatest manhattan distance from origo. Since
+    /// the distance only changes in one direction along a straight line, it is a vertex.
+    pub fn furthest_from_origo(&self) -> Position<T> {
+        // A distance too large for `T::Distance` is larger than any that fits.
+        let mut furthest = (self.start(), self.start().checked_distance_from_origo());
+        for &vertex in &self.vertices[1..] {
+            let distance = vertex.checked_distance_from_origo();
+            let further = match (distance, furthest.1) {
+                (_, None) => false,
+                (None, Some(_)) => true,
+                (Some(distance), Some(furthest)) => distance > furthest,
+            };
+            if further {
+                furthest = (vertex, distance);
+            }
+        }
+        furthest.0
+    }
+
+    /// Iterates over every position the path visits again, in the order it gets there. Only
+    /// positions reached by an earlier step count as visited, so coming back to the start does
+    /// not. A position is repeated for every time it is revisited, and where the path walks
+    /// along itself every position on the shared stretch is revisited.
+    pub fn self_intersections(&self) -> SelfIntersections<'_, T> {
+        SelfIntersections {
+            path: self,
+            next_vertex: 1,
+            stretches: vec![],
+            cursor: None,
+        }
+    }
+}
+
+/// The revisited positions of a `Path`, returned by `Path::self_intersections`.
+pub struct SelfIntersections<'a, T: 'a> {
+    path: &'a Path<T>,
+    /// The index of the vertex the segment after the current one ends at.
+    next_vertex: usize,
+    /// The revisited stretches of the current segment that are left, the nearest last.
+    stretches: Vec<Segment<T>>,
+    /// The next position to return and the stretch it is on.
+    cursor: Option<(Position<T>, Segment<T>)>,
+}
+
+impl<'a, T: Coordinate> Iterator for SelfIntersections<'a, T> {
+    type Item = Position<T>;
+
+    fn next(&mut self) -> Option<Position<T>> {
+        loop {
+            if let Some((position, stretch)) = self.cursor {
+                self.cursor = stretch.direction()
+                    .filter(|_| position != stretch.end())
+                    .map(|direction| {
+                        let mut next = position;
+                        next.walk(&direction, T::ONE);
+                        (next, stretch)
+                    });
+                return Some(position);
+            }
+            if let Some(stretch) = self.stretches.pop() {
+                self.cursor = Some((stretch.start(), stretch));
+                continue;
+            }
+            let vertices = &self.path.vertices;
+            if self.next_vertex >= vertices.len() {
+                return None;
+            }
+            let i = self.next_vertex;
+            self.next_vertex += 1;
+            self.stretches = revisited_stretches(segment(vertices[i - 1], vertices[i]),
+                                                 &vertices[..i]);
+        }
+    }
+}
+
+/// Returns the stretches of `segment` that were already visited by the path through `walked`,
+/// not counting the start of the segment, which is where the previous segment ended. The
+/// stretches don't overlap and are ordered with the one furthest along the segment first.
+fn revisited_stretches<T: Coordinate>(segment: Segment<T>,
+                                      walked: &[Position<T>])
+                                      -> Vec<Segment<T>> {
+    let rest = match after_start(segment) {
+        Some(rest) => rest,
+        None => return vec![],
+    };
+    let along = |position: Position<T>| rest.start().manhattan_distance(position);
+
+    // Every step starts where the one before it ended, so leaving out the starts only leaves
+    // out the start of the path, which no step reached.
+    let mut shared: Vec<Segment<T>> = walked.windows(2)
+        .filter_map(|pair| after_start(self::segment(pair[0], pair[1])))
+        .filter_map(|other| rest.intersection(&other))
+        .collect();
+    shared.sort_by_key(|stretch| along(stretch.start()));
+    let mut stretches: Vec<Segment<T>> = Vec::with_capacity(shared.len());
+    for stretch in shared {
+        match stretches.last_mut() {
+            Some(last) if along(stretch.start()) <= along(last.end()) => {
+                if along(stretch.end()) > along(last.end()) {
+                    *last = self::segment(last.start(), stretch.end());
+                }
+            }
+            _ => stretches.push(stretch),
+        }
+    }
+    stretches.reverse();
+    stretches
+}
+
+/// The positions of the segment except its start, or `None` if that is all of it.
+fn after_start<T: Coordinate>(segment: Segment<T>) -> Option<Segment<T>> {
+    let direction = segment.direction()?;
+    let mut start = segment.start();
+    start.walk(&direction, T::ONE);
+    Some(self::segment(start, segment.end()))
+}
+
+fn segment<T: Coordinate>(start: Position<T>, end: Position<T>) -> Segment<T> {
+    Segment::new(start, end).expect("Steps go along an axis")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use error::Error;
+    use geo::{Direction, Position, Segment, Step};
+    use super::Path;
+
+    fn path(start: Position, steps: &str) -> Path {
+        let steps: Vec<Step> = steps.split(", ").map(|s| Step::from_str(s).unwrap()).collect();
+        Path::new(start, Direction::North, &steps).unwrap()
+    }
+
+    fn self_intersections(path: &Path) -> Vec<(i32, i32)> {
+        path.self_intersections().map(|Position(x, y)| (x, y)).collect()
+    }
+
+    #[test]
+    fn vertices_and_endpoint() {
+        let path = path(Position(1, 1), "R2, L3, L-1, R0");
+        let vertices =
+            [Position(1, 1), Position(3, 1), Position(3, 4), Position(4, 4), Position(4, 4)];
+        assert_eq!(&vertices, path.vertices());
+        assert_eq!(Position(1, 1), path.start());
+        assert_eq!(Position(4, 4), path.endpoint());
+        assert_eq!(4, path.segments().count());
+        assert_eq!(Segment::new(Position(3, 1), Position(3, 4)), path.segments().nth(1));
+    }
+
+    #[test]
+    fn empty() {
+        let path: Path = Path::new(Position(2, -2), Direction::East, &[]).unwrap();
+        assert_eq!(Position(2, -2), path.endpoint());
+        assert_eq!(0, path.length());
+        assert_eq!((Position(2, -2), Position(2, -2)), path.bounding_box());
+        assert_eq!(Position(2, -2), path.furthest_from_origo());
+        assert_eq!(None, path.self_intersections().next());
+    }
+
+    #[test]
+    fn length() {
+        assert_eq!(18, path(Position(0, 0), "R5, R-3, L10, L0").length());
+        let step = Step::from_str("R2147483647").unwrap();
+        let path: Path = Path::new(Position(0, 0), Direction::North, &[step, step]).unwrap();
+        assert_eq!(4294967294, path.length());
+        let path = Path::new(Position(0, 0), Direction::North, &[step, step, step]).unwrap();
+        assert_eq!(None, path.checked_length());
+    }
+
+    #[test]
+    fn overflow() {
+        let steps = [Step::from_str("R2147483647").unwrap(), Step::from_str("L1").unwrap()];
+        let result = Path::new(Position(1, i32::MAX), Direction::North, &steps);
+        assert!(matches!(result, Err(Error::Overflow(_))));
+    }
+
+    #[test]
+    fn bounding_box_and_furthest() {
+        let path = path(Position(0, 0), "L3, R5, R-10, L-1");
+        assert_eq!((Position(-13, 0), Position(0, 5)), path.bounding_box());
+        assert_eq!(Position(-13, 5), path.furthest_from_origo());
+        // The first of two vertices at the same distance.
+        let path = self::path(Position(0, 0), "R2, R4, R4");
+        assert_eq!(Position(2, -4), path.furthest_from_origo());
+    }
+
+    #[test]
+    fn furthest_beyond_distance_type() {
+        let steps = [Step::from_str("L2147483647").unwrap(), Step::from_str("L1").unwrap()];
+        let path = Path::new(Position(-1, -2147483647), Direction::North, &steps).unwrap();
+        assert_eq!(None, path.endpoint().checked_distance_from_origo());
+        assert_eq!(path.endpoint(), path.furthest_from_origo());
+    }
+
+    #[test]
+    fn crossing() {
+        let path = path(Position(0, 0), "R8, R4, R4, R8");
+        assert_eq!(vec![(4, 0)], self_intersections(&path));
+    }
+
+    #[test]
+    fn crossings_in_walking_order() {
+        // The last step reaches the third step before the first.
+        let path = path(Position(0, 0), "R4, L2, L3, R2, R1, R6");
+        assert_eq!(vec![(2, 2), (2, 0)], self_intersections(&path));
+    }
+
+    #[test]
+    fn start_is_not_visited() {
+        let path = path(Position(5, 5), "R2, R2, R2, R2");
+        assert_eq!(Vec::<(i32, i32)>::new(), self_intersections(&path));
+        // Going on past the start reaches what the first step did.
+        let path = self::path(Position(5, 5), "R2, R2, R2, R2, R1");
+        assert_eq!(vec![(6, 5)], self_intersections(&path));
+    }
+
+    #[test]
+    fn walking_along_itself() {
+        // Turning back with a zero step walks back over the first step, then goes on past the
+        // start, which no step reached.
+        let path = path(Position(0, 0), "R3, R0, R5");
+        assert_eq!(vec![(2, 0), (1, 0)], self_intersections(&path));
+    }
+
+    #[test]
+    fn revisited_twice() {
+        // (1, 0) is on two earlier steps when it is passed the third time, but is only
+        // reported once per pass.
+        let path = path(Position(0, 0), "R2, L1, L1, L2, R1, R1, R3");
+        assert_eq!(vec![(1, 0), (1, 0), (2, 0)], self_intersections(&path));
+    }
+
+    #[test]
+    fn huge_coordinates() {
+        let steps: Vec<Step<i64>> = ["R9000000000000000000", "R4", "R4", "R9000000000000000000"]
+            .iter()
+            .map(|s| Step::from_str(s).unwrap())
+            .collect();
+        let path = Path::new(Position(0, 0), Direction::North, &steps).unwrap();
+        assert_eq!(vec![Position(8999999999999999996, 0)],
+                   path.self_intersections().collect::<Vec<_>>());
+        assert_eq!(18000000000000000008, path.length());
+    }
+}
@@ -0,0 +1,209 @@
+use geo::{Coordinate, Direction, Position};
+
+/// A straight line between two positions along one of the axes, including both ends. It is
+/// treated as the positions with integer coordinates on it, so two segments intersect when they
+/// share at least one such position.
+///
+/// Only axis aligned segments are supported, which lets every query be exact without ever
+/// multiplying coordinates, however large they are.
+#[derive(Eq, PartialEq, Hash, Debug, Copy, Clone)]
+pub struct Segment<T = i32> {
+    start: Position<T>,
+    end: Position<T>,
+}
+
+impl<T: Coordinate> Segment<T> {
+    /// Creates the segment from `start` to `end`, or returns `None` if they are not on the same
+    /// row or column.
+    pub fn new(start: Position<T>, end: Position<T>) -> Option<Self> {
+        if start.0 == end.0 || start.1 == end.1 {
+            Some(Segment { start, end })
+        } else {
+            None
+        }
+    }
+
+    /// Creates the segment of a single position.
+    pub fn point(position: Position<T>) -> Self {
+        Segment {
+            start: position,
+            end: position,
+        }
+    }
+
+    pub fn start(&self) -> Position<T> {
+        self.start
+    }
+
+    pub fn end(&self) -> Position<T> {
+        self.end
+    }
+
+    /// The number of steps from start to end.
+    pub fn length(&self) -> T::Distance {
+        // One of the differences is zero, so this never overflows.
+        self.start.manhattan_distance(self.end)
+    }
+
+    pub fn is_point(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// The direction from start to end, or `None` if the segment is a single position.
+    pub fn direction(&self) -> Option<Direction> {
+        use std::cmp::Ordering::*;
+        match (self.end.0.cmp(&self.start.0), self.end.1.cmp(&self.start.1)) {
+            (Greater, _) => Some(Direction::East),
+            (Less, _) => Some(Direction::West),
+            (_, Greater) => Some(Direction::North),
+            (_, Less) => Some(Direction::South),
+            (Equal, Equal) => None,
+        }
+    }
+
+    /// The segment going the other way.
+    pub fn reversed(&self) -> Self {
+        Segment {
+            start: self.end,
+            end: self.start,
+        }
+    }
+
+    pub fn contains(&self, position: Position<T>) -> bool {
+        let (min, max) = self.corners();
+        min.0 <= position.0 && position.0 <= max.0 && min.1 <= position.1 && position.1 <= max.1
+    }
+
+    /// Returns the positions the segments share, or `None` if they share none. Crossing
+    /// segments share a single position, returned as a segment of that point, while parallel
+    /// segments on the same line can share a longer segment. The result goes in the same
+    /// direction as `self`, so its start is the first shared position reached when walking
+    /// along `self`.
+    pub fn intersection(&self, other: &Segment<T>) -> Option<Segment<T>> {
+        // An axis aligned segment is the same as its bounding box, and the intersection of two
+        // such boxes is again one.
+        let (min, max) = self.corners();
+        let (other_min, other_max) = other.corners();
+        let low = Position(min.0.max(other_min.0), min.1.max(other_min.1));
+        let high = Position(max.0.min(other_max.0), max.1.min(other_max.1));
+        if low.0 > high.0 || low.1 > high.1 {
+            return None;
+        }
+        let x = if self.start.0 <= self.end.0 { (low.0, high.0) } else { (high.0, low.0) };
+        let y = if self.start.1 <= self.end.1 { (low.1, high.1) } else { (high.1, low.1) };
+        Some(Segment {
+            start: Position(x.0, y.0),
+            end: Position(x.1, y.1),
+        })
+    }
+
+    /// Returns whether the segments share at least one position.
+    pub fn intersects(&self, other: &Segment<T>) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Returns whether the segments share more than one position, which only segments on the
+    /// same line can.
+    pub fn overlaps(&self, other: &Segment<T>) -> bool {
+        self.intersection(other).is_some_and(|shared| !shared.is_point())
+    }
+
+    /// The south west and north east corners of the bounding box.
+    fn corners(&self) -> (Position<T>, Position<T>) {
+        (Position(self.start.0.min(self.end.0), self.start.1.min(self.end.1)),
+         Position(self.start.0.max(self.end.0), self.start.1.max(self.end.1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::{Direction, Position};
+    use super::Segment;
+
+    fn segment(x1: i32, y1: i32, x2: i32, y2: i32) -> Segment {
+        Segment::new(Position(x1, y1), Position(x2, y2)).unwrap()
+    }
+
+    #[test]
+    fn new_only_along_axes() {
+        assert!(Segment::new(Position(1, 2), Position(1, -5)).is_some());
+        assert!(Segment::new(Position(1, 2), Position(7, 2)).is_some());
+        assert!(Segment::new(Position(1, 2), Position(1, 2)).is_some());
+        assert!(Segment::new(Position(1, 2), Position(2, 3)).is_none());
+    }
+
+    #[test]
+    fn length_and_direction() {
+        assert_eq!(7, segment(1, 2, 1, -5).length());
+        assert_eq!(Some(Direction::South), segment(1, 2, 1, -5).direction());
+        assert_eq!(Some(Direction::West), segment(1, 2, -1, 2).direction());
+        assert_eq!(Some(Direction::North), segment(1, 2, 1, -5).reversed().direction());
+        assert_eq!(None, Segment::<i32>::point(Position(3, 3)).direction());
+        assert_eq!(0, Segment::<i32>::point(Position(3, 3)).length());
+        assert_eq!(u32::MAX, segment(i32::MIN, 0, i32::MAX, 0).length());
+    }
+
+    #[test]
+    fn contains() {
+        let s = segment(4, 1, 4, -3);
+        assert!(s.contains(Position(4, 1)));
+        assert!(s.contains(Position(4, -1)));
+        assert!(s.contains(Position(4, -3)));
+        assert!(!s.contains(Position(4, 2)));
+        assert!(!s.contains(Position(5, 0)));
+    }
+
+    #[test]
+    fn crossing() {
+        let horizontal = segment(-5, 2, 5, 2);
+        let vertical = segment(1, 10, 1, -10);
+        assert_eq!(Some(Segment::point(Position(1, 2))), horizontal.intersection(&vertical));
+        assert_eq!(Some(Segment::point(Position(1, 2))), vertical.intersection(&horizontal));
+        assert!(horizontal.intersects(&vertical));
+        assert!(!horizontal.overlaps(&vertical));
+    }
+
+    #[test]
+    fn touching_ends() {
+        let a = segment(0, 0, 4, 0);
+        assert_eq!(Some(Segment::point(Position(4, 0))), a.intersection(&segment(4, 0, 4, 8)));
+        assert_eq!(Some(Segment::point(Position(4, 0))), a.intersection(&segment(9, 0, 4, 0)));
+        assert_eq!(None, a.intersection(&segment(5, 0, 9, 0)));
+        assert_eq!(None, a.intersection(&segment(2, 1, 2, 5)));
+    }
+
+    #[test]
+    fn parallel() {
+        assert_eq!(None, segment(0, 0, 4, 0).intersection(&segment(0, 1, 4, 1)));
+        assert_eq!(None, segment(0, 0, 0, 4).intersection(&segment(1, 0, 1, 4)));
+    }
+
+    #[test]
+    fn overlap_follows_self() {
+        let a = segment(0, 0, 10, 0);
+        let b = segment(12, 0, 6, 0);
+        assert_eq!(Some(segment(6, 0, 10, 0)), a.intersection(&b));
+        assert_eq!(Some(segment(10, 0, 6, 0)), b.intersection(&a));
+        assert!(a.overlaps(&b) && b.overlaps(&a));
+        let inner = segment(0, 3, 0, 1);
+        assert_eq!(Some(segment(0, 1, 0, 3)), segment(0, -5, 0, 5).intersection(&inner));
+        assert_eq!(Some(inner), segment(0, 5, 0, -5).intersection(&inner));
+    }
+
+    #[test]
+    fn points() {
+        let point = Segment::point(Position(2, 2));
+        assert_eq!(Some(point), point.intersection(&point));
+        assert_eq!(Some(point), segment(2, 0, 2, 9).intersection(&point));
+        assert_eq!(None, segment(3, 0, 3, 9).intersection(&point));
+        assert!(!point.overlaps(&point));
+    }
+
+    #[test]
+    fn huge_coordinates() {
+        let a: Segment<i64> = Segment::new(Position(i64::MIN, 7), Position(i64::MAX, 7)).unwrap();
+        let b = Segment::new(Position(-1, i64::MAX), Position(-1, i64::MIN)).unwrap();
+        assert_eq!(Some(Segment::point(Position(-1, 7))), a.intersection(&b));
+        assert_eq!(u64::MAX, a.length());
+    }
+}
@@ -0,0 +1,107 @@
+use error::Span;
+
+use std::io::{self, BufRead};
+
+/// Normalizes problem input so solvers don't have to care about where it came from. Strips a
+/// UTF-8 byte order mark, converts CRLF line endings to LF and makes non-empty input end with
+/// exactly one newline, removing any trailing empty lines.
+pub fn normalize(input: &str) -> String {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut normalized = input.replace("\r\n", "\n");
+    let content_len = normalized.trim_end_matches('\n').len();
+    normalized.truncate(content_len);
+    if !normalized.is_empty() {
+        normalized.push('\n');
+    }
+    normalized
+}
+
+/// Iterates over the lines read from `reader` together with the span of each line, for solvers
+/// that parse one line at a time. Like `str::lines` the lines don't include their `\n` or
+/// `\r\n`, but the spans count those bytes, so they are offsets into exactly what was read.
+pub fn spanned_lines(reader: &mut dyn BufRead) -> SpannedLines<'_> {
+    SpannedLines {
+        reader,
+        offset: 0,
+        line: 0,
+    }
+}
+
+/// The lines of a reader with their spans, returned by `spanned_lines`.
+pub struct SpannedLines<'a> {
+    reader: &'a mut dyn BufRead,
+    /// The number of bytes read so far.
+    offset: usize,
+    /// The number of lines read so far.
+    line: usize,
+}
+
+impl<'a> Iterator for SpannedLines<'a> {
+    type Item = io::Result<(String, Span)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+        let read = match self.reader.read_line(&mut line) {
+            Ok(0) => return None,
+            Ok(read) => read,
+            Err(e) => return Some(Err(e)),
+        };
+        let start = self.offset;
+        self.offset += read;
+        self.line += 1;
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        let span = Span {
+            start,
+            end: start + line.len(),
+            line: self.line,
+            column: 1,
+        };
+        Some(Ok((line, span)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use error::Span;
+    use super::{normalize, spanned_lines};
+
+    #[test]
+    fn strips_byte_order_mark() {
+        assert_eq!("R2, L3\n", normalize("\u{feff}R2, L3\n"));
+    }
+
+    #[test]
+    fn converts_crlf() {
+        assert_eq!("UL\nRD\n", normalize("UL\r\nRD\r\n"));
+    }
+
+    #[test]
+    fn exactly_one_trailing_newline() {
+        assert_eq!("1 2 3\n", normalize("1 2 3"));
+        assert_eq!("1 2 3\n", normalize("1 2 3\n\n\r\n"));
+        assert_eq!("", normalize("\n\n"));
+        assert_eq!("", normalize(""));
+    }
+
+    #[test]
+    fn keeps_other_whitespace() {
+        assert_eq!("  5 10 25\n\n 3 4 5 \n", normalize("  5 10 25\n\n 3 4 5 \n"));
+    }
+
+    #[test]
+    fn spanned_lines_count_line_endings() {
+        let mut reader = &b"UL\r\nRDD\n\nU"[..];
+        let lines: Vec<(String, Span)> = spanned_lines(&mut reader).map(Result::unwrap).collect();
+        let span = |start, end, line| Span { start, end, line, column: 1 };
+        assert_eq!(vec![("UL".to_owned(), span(0, 2, 1)),
+                        ("RDD".to_owned(), span(4, 7, 2)),
+                        ("".to_owned(), span(8, 8, 3)),
+                        ("U".to_owned(), span(9, 10, 4))],
+                   lines);
+    }
+}
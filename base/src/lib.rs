@@ -1,26 +1,157 @@
+use std::any::Any;
 use std::fmt;
+use std::io::BufRead;
 use std::str::FromStr;
 
+mod error;
 pub mod geo;
+pub mod input;
+mod params;
+mod registry;
+mod solution;
 
+pub use error::{Error, ParseError, Span};
+pub use params::{parse_assignment, ParamKind, ParamSpec, ParamValue, Params};
+pub use registry::{Registration, Registry};
+pub use solution::Solution;
+
+/// A solver for both parts of one day's problem. The input is parsed once with `parse` and the
+/// result can then be used to solve any number of parts.
 pub trait ProblemSolver {
-    fn solve(&self, part: Part, input: String) -> Result<String, String>;
+    /// The problem input after parsing.
+    type Input: 'static;
+
+    /// The title of the puzzle, such as "No Time for a Taxicab".
+    fn title(&self) -> &'static str;
+
+    /// The parts of the problem this solver can solve. Defaults to both parts.
+    fn parts(&self) -> &'static [Part] {
+        Part::all()
+    }
+
+    /// The parameters the solver accepts, such as a value that differs between the worked
+    /// example and the real input. Defaults to no parameters.
+    fn params(&self) -> &'static [ParamSpec] {
+        &[]
+    }
+
+    /// Configures the solver with values for the parameters it declares in `params`, already
+    /// validated. Called before parsing and solving. The default implementation does nothing.
+    fn configure(&mut self, _params: &Params) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error>;
+
+    /// Parses the input from a reader. The default implementation reads everything into memory
+    /// and calls `parse`. Line oriented solvers can override it to parse one line at a time.
+    fn parse_reader(&self, reader: &mut dyn BufRead) -> Result<Self::Input, Error> {
+        let mut input = String::new();
+        reader.read_to_string(&mut input)?;
+        self.parse(&input)
+    }
+
+    /// Parses the input only to find the errors in it. The default implementation returns the
+    /// first error from `parse`. Solvers that can continue parsing after an error should
+    /// override it to report every error.
+    fn check(&self, input: &str) -> Vec<ParseError> {
+        match self.parse(input) {
+            Ok(_) => vec![],
+            Err(Error::Parse(e)) => vec![e],
+            Err(e) => vec![ParseError::new(e.to_string())],
+        }
+    }
+
+    fn solve(&self, part: Part, input: &Self::Input) -> Result<Solution, Error>;
+}
+
+/// Object safe version of `ProblemSolver`, with the parsed input type erased. Implemented for
+/// all `ProblemSolver`s, so the CLI can handle the solvers for all days the same way.
+pub trait DynProblemSolver {
+    fn title(&self) -> &'static str;
+
+    fn parts(&self) -> &'static [Part];
+
+    fn params(&self) -> &'static [ParamSpec];
+
+    fn configure(&mut self, params: &Params) -> Result<(), Error>;
+
+    fn parse(&self, input: &str) -> Result<ParsedInput, Error>;
+
+    fn parse_reader(&self, reader: &mut dyn BufRead) -> Result<ParsedInput, Error>;
+
+    fn check(&self, input: &str) -> Vec<ParseError>;
+
+    /// Solves the given part. Fails with `Error::Unsupported` if the solver can't solve that
+    /// part. Panics if `input` was not parsed by this solver.
+    fn solve(&self, part: Part, input: &ParsedInput) -> Result<Solution, Error>;
+}
+
+impl<S: ProblemSolver> DynProblemSolver for S {
+    fn title(&self) -> &'static str {
+        ProblemSolver::title(self)
+    }
+
+    fn parts(&self) -> &'static [Part] {
+        ProblemSolver::parts(self)
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        ProblemSolver::params(self)
+    }
+
+    fn configure(&mut self, params: &Params) -> Result<(), Error> {
+        ProblemSolver::configure(self, params)
+    }
+
+    fn parse(&self, input: &str) -> Result<ParsedInput, Error> {
+        ProblemSolver::parse(self, input).map(|input| ParsedInput(Box::new(input)))
+    }
+
+    fn parse_reader(&self, reader: &mut dyn BufRead) -> Result<ParsedInput, Error> {
+        ProblemSolver::parse_reader(self, reader).map(|input| ParsedInput(Box::new(input)))
+    }
+
+    fn check(&self, input: &str) -> Vec<ParseError> {
+        ProblemSolver::check(self, input)
+    }
+
+    fn solve(&self, part: Part, input: &ParsedInput) -> Result<Solution, Error> {
+        if !ProblemSolver::parts(self).contains(&part) {
+            return Err(Error::Unsupported(format!("There is no solver for part {}", part)));
+        }
+        let input = input.0
+            .downcast_ref::<S::Input>()
+            .expect("Input was parsed by a different solver");
+        ProblemSolver::solve(self, part, input)
+    }
 }
 
-#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+/// Problem input parsed by a `DynProblemSolver`.
+pub struct ParsedInput(Box<dyn Any>);
+
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone, Debug)]
 /// Represents part one or two of a problem in AoC
 pub enum Part {
     One,
     Two,
 }
 
+impl Part {
+    /// Both parts, in order.
+    pub fn all() -> &'static [Part] {
+        static ALL: [Part; 2] = [Part::One, Part::Two];
+        &ALL
+    }
+}
+
 impl FromStr for Part {
-    type Err = String;
+    type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "1" => Ok(Part::One),
-            "2" => Ok(Part::Two),
-            _ => Err(format!("Invalid part, must be 1 or 2, was {}", s)),
+            "1" | "one" => Ok(Part::One),
+            "2" | "two" => Ok(Part::Two),
+            _ => Err(Error::InvalidArgument(format!("Invalid part, must be 1 or 2, was {}", s))),
         }
     }
 }
@@ -35,3 +166,85 @@ impl fmt::Display for Part {
                })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{DynProblemSolver, Error, Part, ProblemSolver, Solution};
+
+    struct Lines;
+
+    impl ProblemSolver for Lines {
+        type Input = Vec<String>;
+
+        fn title(&self) -> &'static str {
+            "Lines"
+        }
+
+        fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+            Ok(input.lines().map(|line| line.to_owned()).collect())
+        }
+
+        fn solve(&self, part: Part, lines: &Self::Input) -> Result<Solution, Error> {
+            match part {
+                Part::One => Ok(Solution::from(lines.len())),
+                Part::Two => Ok(Solution::from(lines.join(""))),
+            }
+        }
+    }
+
+    #[test]
+    fn dyn_solver_reuses_parsed_input() {
+        let solver: Box<dyn DynProblemSolver> = Box::new(Lines);
+        let input = solver.parse("ab\ncd\n").unwrap();
+        assert_eq!(Solution::UInt(2), solver.solve(Part::One, &input).unwrap());
+        assert_eq!(Solution::from("abcd"), solver.solve(Part::Two, &input).unwrap());
+    }
+
+    #[test]
+    fn part_from_str_spelled_out() {
+        assert_eq!(Part::One, "one".parse::<Part>().unwrap());
+        assert_eq!(Part::Two, "two".parse::<Part>().unwrap());
+    }
+
+    #[test]
+    fn part_from_str_invalid_argument() {
+        assert!(matches!("3".parse::<Part>(), Err(Error::InvalidArgument(_))));
+    }
+
+    struct FirstPartOnly;
+
+    impl ProblemSolver for FirstPartOnly {
+        type Input = ();
+
+        fn title(&self) -> &'static str {
+            "First part only"
+        }
+
+        fn parts(&self) -> &'static [Part] {
+            &[Part::One]
+        }
+
+        fn parse(&self, _input: &str) -> Result<Self::Input, Error> {
+            Ok(())
+        }
+
+        fn solve(&self, _part: Part, _input: &Self::Input) -> Result<Solution, Error> {
+            Ok(Solution::from(1u8))
+        }
+    }
+
+    #[test]
+    fn dyn_solver_rejects_unsupported_part() {
+        let solver: Box<dyn DynProblemSolver> = Box::new(FirstPartOnly);
+        let input = solver.parse("").unwrap();
+        assert!(solver.solve(Part::One, &input).is_ok());
+        assert!(matches!(solver.solve(Part::Two, &input), Err(Error::Unsupported(_))));
+    }
+
+    #[test]
+    fn default_parse_reader() {
+        let solver: Box<dyn DynProblemSolver> = Box::new(Lines);
+        let input = solver.parse_reader(&mut &b"ab\ncd\nef"[..]).unwrap();
+        assert_eq!(Solution::UInt(3), solver.solve(Part::One, &input).unwrap());
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use error::Error;
+use geo::{Direction, Position};
+
+/// The kind of value a solver parameter takes.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ParamKind {
+    /// A signed integer.
+    Int,
+    /// A position written as `x,y`.
+    Position,
+    /// A direction, one of `N`, `E`, `S` or `W`.
+    Direction,
+    /// One of the given words.
+    Choice(&'static [&'static str]),
+}
+
+impl fmt::Display for ParamKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParamKind::Int => write!(f, "integer"),
+            ParamKind::Position => write!(f, "position x,y"),
+            ParamKind::Direction => write!(f, "direction N, E, S or W"),
+            ParamKind::Choice(choices) => write!(f, "one of {}", choices.join(", ")),
+        }
+    }
+}
+
+/// Declares a parameter a solver accepts.
+#[derive(Debug, Clone, Copy)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    /// The value used when the parameter is not given. Must be valid for `kind`.
+    pub default: &'static str,
+    pub help: &'static str,
+}
+
+impl ParamSpec {
+    /// Parses and validates a value for this parameter.
+    pub fn parse(&self, value: &str) -> Result<ParamValue, Error> {
+        let invalid = || {
+            Error::InvalidArgument(format!("Invalid value {:?} for parameter {}, expected {}",
+                                           value,
+                                           self.name,
+                                           self.kind))
+        };
+        match self.kind {
+            ParamKind::Int => i64::from_str(value).map(ParamValue::Int).map_err(|_| invalid()),
+            ParamKind::Position => {
+                Position::from_str(value).map(ParamValue::Position).map_err(|_| invalid())
+            }
+            ParamKind::Direction => {
+                // `Direction::from_str` also takes turns and anything after the first letter.
+                let direction = match value {
+                    "N" => Direction::North,
+                    "E" => Direction::East,
+                    "S" => Direction::South,
+                    "W" => Direction::West,
+                    _ => return Err(invalid()),
+                };
+                Ok(ParamValue::Direction(direction))
+            }
+            ParamKind::Choice(choices) => {
+                choices.iter()
+                    .find(|&&choice| choice == value)
+                    .map(|&choice| ParamValue::Choice(choice))
+                    .ok_or_else(invalid)
+            }
+        }
+    }
+}
+
+/// A validated parameter value.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ParamValue {
+    Int(i64),
+    Position(Position),
+    Direction(Direction),
+    Choice(&'static str),
+}
+
+/// Writes the value the way it is given, so parsing it again gives the same value.
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParamValue::Int(n) => write!(f, "{}", n),
+            ParamValue::Position(Position(x, y)) => write!(f, "{},{}", x, y),
+            ParamValue::Direction(direction) => {
+                let letter = match direction {
+                    Direction::North => 'N',
+                    Direction::East => 'E',
+                    Direction::South => 'S',
+                    Direction::West => 'W',
+                };
+                write!(f, "{}", letter)
+            }
+            ParamValue::Choice(choice) => write!(f, "{}", choice),
+        }
+    }
+}
+
+/// The values of all parameters a solver declares, validated against their declarations.
+///
+/// The typed getters panic if the parameter is not declared with the matching kind, since that
+/// is a mistake in the solver and not in the given values.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Params {
+    values: BTreeMap<&'static str, ParamValue>,
+}
+
+impl Params {
+    /// Validates the given `(name, value)` pairs against the declared parameters. Parameters
+    /// that are not given get their default value. If a name is given more than once the last
+    /// value is used.
+    pub fn new(specs: &[ParamSpec], values: &[(String, String)]) -> Result<Self, Error> {
+        let mut params = Params::default();
+        for spec in specs {
+            let value = spec.parse(spec.default)
+                .unwrap_or_else(|e| panic!("Invalid default for parameter {}: {}", spec.name, e));
+            params.values.insert(spec.name, value);
+        }
+        for (name, value) in values {
+            let spec = specs.iter().find(|spec| spec.name == name).ok_or_else(|| {
+                    let names: Vec<&str> = specs.iter().map(|spec| spec.name).collect();
+                    let known = if names.is_empty() {
+                        "the solver takes no parameters".to_owned()
+                    } else {
+                        format!("known parameters are {}", names.join(", "))
+                    };
+                    Error::InvalidArgument(format!("Unknown parameter {}, {}", name, known))
+                })?;
+            params.values.insert(spec.name, spec.parse(value)?);
+        }
+        Ok(params)
+    }
+
+    pub fn get(&self, name: &str) -> Option<ParamValue> {
+        self.values.get(name).cloned()
+    }
+
+    pub fn int(&self, name: &str) -> i64 {
+        match self.get(name) {
+            Some(ParamValue::Int(n)) => n,
+            value => panic!("Parameter {} is not an integer: {:?}", name, value),
+        }
+    }
+
+    pub fn position(&self, name: &str) -> Position {
+        match self.get(name) {
+            Some(ParamValue::Position(position)) => position,
+            value => panic!("Parameter {} is not a position: {:?}", name, value),
+        }
+    }
+
+    pub fn direction(&self, name: &str) -> Direction {
+        match self.get(name) {
+            Some(ParamValue::Direction(direction)) => direction,
+            value => panic!("Parameter {} is not a direction: {:?}", name, value),
+        }
+    }
+
+    pub fn choice(&self, name: &str) -> &'static str {
+        match self.get(name) {
+            Some(ParamValue::Choice(choice)) => choice,
+            value => panic!("Parameter {} is not a choice: {:?}", name, value),
+        }
+    }
+}
+
+/// Writes every parameter as `name=value`, sorted by name and separated by spaces. Parameters
+/// that resolve to the same values are written the same, whether they were given or defaults.
+impl fmt::Display for Params {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (name, value)) in self.values.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}={}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits a `key=value` string into its key and value.
+pub fn parse_assignment(s: &str) -> Result<(String, String), Error> {
+    let mut parts = s.splitn(2, '=');
+    match (parts.next(), parts.next()) {
+        (Some(key), Some(value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_owned(), value.trim().to_owned()))
+        }
+        _ => Err(Error::InvalidArgument(format!("Invalid parameter {:?}, expected key=value", s))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Error;
+    use geo::{Direction, Position};
+    use super::{parse_assignment, ParamKind, ParamSpec, ParamValue, Params};
+
+    static SPECS: [ParamSpec; 3] = [ParamSpec {
+                                        name: "start",
+                                        kind: ParamKind::Position,
+                                        default: "0,0",
+                                        help: "Where to start",
+                                    },
+                                    ParamSpec {
+                                        name: "heading",
+                                        kind: ParamKind::Direction,
+                                        default: "N",
+                                        help: "Where to look",
+                                    },
+                                    ParamSpec {
+                                        name: "mode",
+                                        kind: ParamKind::Choice(&["fast", "slow"]),
+                                        default: "fast",
+                                        help: "How to go",
+                                    }];
+
+    fn assignments(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|&(k, v)| (k.to_owned(), v.to_owned())).collect()
+    }
+
+    #[test]
+    fn defaults() {
+        let params = Params::new(&SPECS, &[]).unwrap();
+        assert_eq!(Position(0, 0), params.position("start"));
+        assert_eq!(Direction::North, params.direction("heading"));
+        assert_eq!("fast", params.choice("mode"));
+    }
+
+    #[test]
+    fn given_values_override_defaults() {
+        let values = assignments(&[("start", "3,-4"), ("mode", "slow"), ("mode", "fast")]);
+        let params = Params::new(&SPECS, &values).unwrap();
+        assert_eq!(Position(3, -4), params.position("start"));
+        assert_eq!(Some(ParamValue::Choice("fast")), params.get("mode"));
+    }
+
+    #[test]
+    fn unknown_parameter() {
+        match Params::new(&SPECS, &assignments(&[("speed", "1")])) {
+            Err(Error::InvalidArgument(msg)) => {
+                assert_eq!("Unknown parameter speed, known parameters are start, heading, mode",
+                           msg)
+            }
+            _ => panic!("Expected an invalid argument error"),
+        }
+    }
+
+    #[test]
+    fn invalid_values() {
+        for &(name, value) in &[("start", "3"),
+                                ("heading", "X"),
+                                ("heading", "Nx"),
+                                ("heading", "U"),
+                                ("heading", "North"),
+                                ("mode", "medium")] {
+            let result = Params::new(&SPECS, &assignments(&[(name, value)]));
+            assert!(matches!(result, Err(Error::InvalidArgument(_))), "{}={}", name, value);
+        }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("heading=N mode=fast start=0,0", Params::new(&SPECS, &[]).unwrap().to_string());
+        let values = assignments(&[("mode", "slow"), ("heading", "W"), ("start", "-1,2")]);
+        assert_eq!("heading=W mode=slow start=-1,2",
+                   Params::new(&SPECS, &values).unwrap().to_string());
+        let defaults = assignments(&[("mode", "fast")]);
+        assert_eq!(Params::new(&SPECS, &[]).unwrap().to_string(),
+                   Params::new(&SPECS, &defaults).unwrap().to_string());
+    }
+
+    #[test]
+    fn int_parameter() {
+        let specs = [ParamSpec {
+                         name: "n",
+                         kind: ParamKind::Int,
+                         default: "-7",
+                         help: "",
+                     }];
+        assert_eq!(-7, Params::new(&specs, &[]).unwrap().int("n"));
+    }
+
+    #[test]
+    fn assignment() {
+        assert_eq!(("start".to_owned(), "1,2".to_owned()),
+                   parse_assignment("start=1,2").unwrap());
+        assert_eq!(("a".to_owned(), "b=c".to_owned()), parse_assignment("a=b=c").unwrap());
+        assert!(parse_assignment("start").is_err());
+        assert!(parse_assignment("=1").is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+
+use DynProblemSolver;
+use error::Error;
+
+/// A solver registered for one day, together with information about it.
+#[derive(Clone, Copy)]
+pub struct Registration {
+    pub year: u16,
+    pub day: u8,
+    /// Name of the crate the solver is implemented in.
+    pub crate_name: &'static str,
+    /// Creates the solver.
+    pub create: fn() -> Box<dyn DynProblemSolver>,
+}
+
+/// Keeps track of which days have a solver. Every day crate has a `register` function adding
+/// its solver, so the CLI never has to know about the individual days.
+#[derive(Default)]
+pub struct Registry {
+    solvers: BTreeMap<(u16, u8), Registration>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the solver for the given day. Panics if the day already has a solver, since
+    /// that means two crates claim to solve the same problem.
+    pub fn register(&mut self,
+                    year: u16,
+                    day: u8,
+                    crate_name: &'static str,
+                    create: fn() -> Box<dyn DynProblemSolver>) {
+        let registration = Registration {
+            year,
+            day,
+            crate_name,
+            create,
+        };
+        if let Some(old) = self.solvers.insert((year, day), registration) {
+            panic!("Both {} and {} register a solver for {} day {}",
+                   old.crate_name,
+                   crate_name,
+                   year,
+                   day);
+        }
+    }
+
+    pub fn registration(&self, year: u16, day: u8) -> Option<&Registration> {
+        self.solvers.get(&(year, day))
+    }
+
+    /// Creates the solver for the given day. The error lists the days that do have a solver.
+    pub fn get(&self, year: u16, day: u8) -> Result<Box<dyn DynProblemSolver>, Error> {
+        match self.registration(year, day) {
+            Some(registration) => Ok((registration.create)()),
+            None => {
+                let days: Vec<String> = self.days(year).iter().map(|day| day.to_string()).collect();
+                let available = if days.is_empty() {
+                    format!("there are no solvers for {}", year)
+                } else {
+                    format!("available days are {}", days.join(", "))
+                };
+                Err(Error::Unsupported(format!("No solver for day {}, {}", day, available)))
+            }
+        }
+    }
+
+    /// Returns the days of the given year that have a solver, in order.
+    pub fn days(&self, year: u16) -> Vec<u8> {
+        self.registrations().filter(|r| r.year == year).map(|r| r.day).collect()
+    }
+
+    /// Iterates over all registered solvers, ordered by year and day.
+    pub fn registrations(&self) -> impl Iterator<Item = &Registration> {
+        self.solvers.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {DynProblemSolver, Error, Part, ProblemSolver, Solution};
+    use super::Registry;
+
+    struct Constant(u32);
+
+    impl ProblemSolver for Constant {
+        type Input = ();
+
+        fn title(&self) -> &'static str {
+            "Constant"
+        }
+
+        fn parse(&self, _input: &str) -> Result<Self::Input, Error> {
+            Ok(())
+        }
+
+        fn solve(&self, _part: Part, _input: &Self::Input) -> Result<Solution, Error> {
+            Ok(Solution::from(self.0))
+        }
+    }
+
+    fn three() -> Box<dyn DynProblemSolver> {
+        Box::new(Constant(3))
+    }
+
+    fn seven() -> Box<dyn DynProblemSolver> {
+        Box::new(Constant(7))
+    }
+
+    fn registry() -> Registry {
+        let mut registry = Registry::new();
+        registry.register(2016, 7, "day7", seven);
+        registry.register(2016, 3, "day3", three);
+        registry
+    }
+
+    #[test]
+    fn get_registered() {
+        let solver = registry().get(2016, 3).unwrap();
+        let input = solver.parse("").unwrap();
+        assert_eq!(Solution::UInt(3), solver.solve(Part::One, &input).unwrap());
+    }
+
+    #[test]
+    fn days_in_order() {
+        assert_eq!(vec![3, 7], registry().days(2016));
+        assert!(registry().days(2015).is_empty());
+    }
+
+    #[test]
+    fn unknown_day_lists_available() {
+        match registry().get(2016, 4) {
+            Err(Error::Unsupported(msg)) => {
+                assert_eq!("No solver for day 4, available days are 3, 7", msg)
+            }
+            _ => panic!("Expected an unsupported error"),
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn register_twice() {
+        let mut registry = registry();
+        registry.register(2016, 3, "other", seven);
+    }
+}
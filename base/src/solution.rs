@@ -0,0 +1,167 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// The answer to one part of a problem.
+///
+/// Two numeric solutions are equal if they have the same value, regardless of if they are
+/// signed or not.
+#[derive(Debug, Clone)]
+pub enum Solution {
+    Int(i64),
+    UInt(u64),
+    Text(String),
+    /// An answer spanning multiple lines, such as a grid of characters that has to be read by a
+    /// human to get the actual answer.
+    Lines(Vec<String>),
+}
+
+impl Solution {
+    /// Returns the value as a signed integer if it is numeric and fits.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Solution::Int(n) => Some(n),
+            Solution::UInt(n) if n <= i64::MAX as u64 => Some(n as i64),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as an unsigned integer if it is numeric and not negative.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Solution::Int(n) if n >= 0 => Some(n as u64),
+            Solution::UInt(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        match *self {
+            Solution::Int(_) | Solution::UInt(_) => true,
+            Solution::Text(_) | Solution::Lines(_) => false,
+        }
+    }
+}
+
+impl PartialEq for Solution {
+    fn eq(&self, other: &Solution) -> bool {
+        match (self, other) {
+            (Solution::Text(a), Solution::Text(b)) => a == b,
+            (Solution::Lines(a), Solution::Lines(b)) => a == b,
+            (a, b) if a.is_numeric() && b.is_numeric() => {
+                match (a.as_i64(), b.as_i64()) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => a.as_u64() == b.as_u64(),
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Solution {}
+
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Solution::Int(n) => write!(f, "{}", n),
+            Solution::UInt(n) => write!(f, "{}", n),
+            Solution::Text(ref s) => write!(f, "{}", s),
+            Solution::Lines(ref lines) => write!(f, "{}", lines.join("\n")),
+        }
+    }
+}
+
+/// Parses the text representation of a solution, as written by `Display`. Integers become
+/// numeric solutions, text with line breaks becomes `Lines` and anything else `Text`.
+impl FromStr for Solution {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(n) = u64::from_str(s) {
+            Ok(Solution::UInt(n))
+        } else if let Ok(n) = i64::from_str(s) {
+            Ok(Solution::Int(n))
+        } else if s.contains('\n') {
+            Ok(Solution::Lines(s.lines().map(|line| line.to_owned()).collect()))
+        } else {
+            Ok(Solution::Text(s.to_owned()))
+        }
+    }
+}
+
+macro_rules! impl_from_unsigned {
+    ($($t:ty),*) => ($(
+        impl From<$t> for Solution {
+            fn from(n: $t) -> Self {
+                Solution::UInt(n as u64)
+            }
+        }
+    )*)
+}
+
+macro_rules! impl_from_signed {
+    ($($t:ty),*) => ($(
+        impl From<$t> for Solution {
+            fn from(n: $t) -> Self {
+                Solution::Int(n as i64)
+            }
+        }
+    )*)
+}
+
+impl_from_unsigned!(u8, u16, u32, u64, usize);
+impl_from_signed!(i8, i16, i32, i64, isize);
+
+impl From<String> for Solution {
+    fn from(s: String) -> Self {
+        Solution::Text(s)
+    }
+}
+
+impl<'a> From<&'a str> for Solution {
+    fn from(s: &'a str) -> Self {
+        Solution::Text(s.to_owned())
+    }
+}
+
+impl From<Vec<String>> for Solution {
+    fn from(lines: Vec<String>) -> Self {
+        Solution::Lines(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use super::Solution;
+
+    #[test]
+    fn numeric_equality_ignores_signedness() {
+        assert_eq!(Solution::Int(301), Solution::UInt(301));
+        assert_eq!(Solution::from(301u32), Solution::from(301i64));
+        assert!(Solution::Int(-1) != Solution::UInt(u64::MAX));
+    }
+
+    #[test]
+    fn text_not_equal_to_number() {
+        assert!(Solution::from("301") != Solution::from(301u32));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("-12", Solution::Int(-12).to_string());
+        assert_eq!("9A7DC", Solution::from("9A7DC").to_string());
+        let lines = Solution::Lines(vec!["#.#".to_owned(), ".#.".to_owned()]);
+        assert_eq!("#.#\n.#.", lines.to_string());
+    }
+
+    #[test]
+    fn from_str_roundtrip() {
+        let solutions = [Solution::UInt(97289),
+                         Solution::Int(-5),
+                         Solution::from("9A7DC"),
+                         Solution::Lines(vec!["#.#".to_owned(), ".#.".to_owned()])];
+        for solution in &solutions {
+            assert_eq!(*solution, Solution::from_str(&solution.to_string()).unwrap());
+        }
+    }
+}
@@ -1,95 +1,175 @@
 extern crate base;
 
-use base::{Part, ProblemSolver};
-use base::geo::{Step, Direction, Position};
+use base::{DynProblemSolver, Error, ParamKind, ParamSpec, Params, ParseError, Part, ProblemSolver,
+           Registry, Solution};
+use base::geo::{Direction, Path, Position, Step};
 
-use std::collections::HashSet;
 use std::str::FromStr;
 
-pub fn get_solver() -> Box<ProblemSolver> {
-    Box::new(Day1)
+pub fn get_solver() -> Box<dyn DynProblemSolver> {
+    let (start, heading) = start_values();
+    Box::new(Day1 { start, heading })
 }
 
-struct Day1;
+/// Registers the solver for this day.
+pub fn register(registry: &mut Registry) {
+    registry.register(2016, 1, env!("CARGO_PKG_NAME"), get_solver);
+}
+
+static PARAMS: [ParamSpec; 2] = [ParamSpec {
+                                     name: "start",
+                                     kind: ParamKind::Position,
+                                     default: "0,0",
+                                     help: "The position to start walking from.",
+                                 },
+                                 ParamSpec {
+                                     name: "heading",
+                                     kind: ParamKind::Direction,
+                                     default: "N",
+                                     help: "The direction to face before the first turn.",
+                                 }];
+
+struct Day1 {
+    start: Position,
+    heading: Direction,
+}
 
 impl ProblemSolver for Day1 {
-    fn solve(&self, part: Part, input: String) -> Result<String, String> {
-        let steps = parse_input(input)?;
+    type Input = Vec<Step>;
+
+    fn title(&self) -> &'static str {
+        "No Time for a Taxicab"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &PARAMS
+    }
+
+    fn configure(&mut self, params: &Params) -> Result<(), Error> {
+        self.start = params.position("start");
+        self.heading = params.direction("heading");
+        Ok(())
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        parse_input(input).map_err(|mut errors| Error::from(errors.remove(0)))
+    }
+
+    fn check(&self, input: &str) -> Vec<ParseError> {
+        parse_input(input).err().unwrap_or_default()
+    }
+
+    fn solve(&self, part: Part, steps: &Self::Input) -> Result<Solution, Error> {
         match part {
-            Part::One => Ok(distance_to_endpoint(&steps).to_string()),
-            Part::Two => Ok(distance_to_first_path_overlap(&steps)?.to_string()),
+            Part::One => {
+                let distance = distance_to_endpoint(steps, self.start, self.heading)?;
+                Ok(Solution::from(distance))
+            }
+            Part::Two => {
+                let distance = distance_to_first_path_overlap(steps, self.start, self.heading)?;
+                Ok(Solution::from(distance))
+            }
         }
     }
 }
 
-fn parse_input(input: String) -> Result<Vec<Step>, String> {
+/// Parses the comma separated steps. Returns every step that could not be parsed, with spans
+/// relative to `input`.
+fn parse_input(input: &str) -> Result<Vec<Step>, Vec<ParseError>> {
     let mut steps = vec![];
-    for step_str in input.split(",") {
-        let step = Step::from_str(step_str.trim())?;
-        steps.push(step);
+    let mut errors = vec![];
+    for step_str in input.split(',') {
+        let step_str = step_str.trim();
+        match Step::from_str(step_str) {
+            Ok(step) => steps.push(step),
+            Err(e) => errors.push(e.within(input, step_str)),
+        }
     }
-    Ok(steps)
+    if errors.is_empty() { Ok(steps) } else { Err(errors) }
 }
 
-fn distance_to_endpoint(steps: &[Step]) -> u32 {
-    let (mut position, mut direction) = start_values();
-    for step in steps {
-        direction = direction.turn(&step.turn());
-        position.walk(&direction, step.distance());
-    }
-    position.distance_from_origo()
+fn distance_to_endpoint(steps: &[Step],
+                        start: Position,
+                        heading: Direction)
+                        -> Result<u32, Error> {
+    distance_from_origo(Path::new(start, heading, steps)?.endpoint())
 }
 
-fn distance_to_first_path_overlap(steps: &[Step]) -> Result<u32, String> {
-    let (mut position, mut direction) = start_values();
-    let mut visited = HashSet::new();
-    for step in steps {
-        direction = direction.turn(&step.turn());
-        let normalized_distance = if step.distance() > 0 { 1 } else { -1 };
-        for _ in 0..step.distance() {
-            position.walk(&direction, normalized_distance);
-            if !visited.insert(position) {
-                return Ok(position.distance_from_origo());
-            }
-        }
+/// Returns the distance to the first position visited twice. Only positions reached by a step
+/// count as visited, so returning to the start is not enough.
+fn distance_to_first_path_overlap(steps: &[Step],
+                                  start: Position,
+                                  heading: Direction)
+                                  -> Result<u32, Error> {
+    let path = Path::new(start, heading, steps)?;
+    match path.self_intersections().next() {
+        Some(revisited) => distance_from_origo(revisited),
+        None => Err(Error::NoSolution("The given steps does not cross its own path".to_owned())),
     }
-    Err("The given steps does not cross its own path".to_owned())
 }
 
+fn distance_from_origo(position: Position) -> Result<u32, Error> {
+    position.checked_distance_from_origo()
+        .ok_or_else(|| Error::Overflow(format!("measuring the distance to {:?}", position)))
+}
+
+/// The start position and heading used when no parameters are given.
 fn start_values() -> (Position, Direction) {
     (Position(0, 0), Direction::North)
 }
 
 #[cfg(test)]
 mod tests {
-    use base::geo::Step;
+    use base::{Error, Params, Part, Solution};
+    use base::geo::{Direction, Position, Step};
     use std::str::FromStr;
-    use super::{distance_to_endpoint, distance_to_first_path_overlap};
+    use super::{distance_to_endpoint, distance_to_first_path_overlap, get_solver, parse_input};
+
+    const ORIGIN: Position = Position(0, 0);
+
+    #[test]
+    fn configured_start() {
+        let mut solver = get_solver();
+        let params = [("start".to_owned(), "10,-3".to_owned()),
+                      ("heading".to_owned(), "E".to_owned())];
+        solver.configure(&Params::new(solver.params(), &params).unwrap()).unwrap();
+        let input = solver.parse("R2, L5").unwrap();
+        assert_eq!(Solution::UInt(20), solver.solve(Part::One, &input).unwrap());
+    }
+
+    #[test]
+    fn parse_input_reports_every_error() {
+        let errors = parse_input("R2, X3,\nR4x, L1,").unwrap_err();
+        let positions: Vec<_> = errors.iter()
+            .map(|e| e.span().map(|span| (span.line, span.column)).unwrap())
+            .collect();
+        assert_eq!(vec![(1, 5), (2, 2), (2, 9)], positions);
+    }
 
     #[test]
     fn stand_still() {
-        let result = distance_to_endpoint(&[]);
+        let result = distance_to_endpoint(&[], ORIGIN, Direction::North).unwrap();
         assert_eq!(0, result);
     }
 
     #[test]
     fn distance_to_endpoint_single_step() {
         let step = Step::from_str("R1").unwrap();
-        let result = distance_to_endpoint(&[step]);
+        let result = distance_to_endpoint(&[step], ORIGIN, Direction::North).unwrap();
         assert_eq!(1, result);
     }
 
     #[test]
     fn distance_to_endpoint_two_steps() {
         let steps = [Step::from_str("R100").unwrap(), Step::from_str("R50").unwrap()];
-        let result = distance_to_endpoint(&steps);
+        let result = distance_to_endpoint(&steps, ORIGIN, Direction::North).unwrap();
         assert_eq!(150, result);
     }
 
     #[test]
     fn distance_to_endpoint_negative() {
         let steps = [Step::from_str("L-40").unwrap(), Step::from_str("R-20").unwrap()];
-        let result = distance_to_endpoint(&steps);
+        let result = distance_to_endpoint(&steps, ORIGIN, Direction::North).unwrap();
         assert_eq!(60, result);
     }
 
@@ -98,15 +178,62 @@ mod tests {
         let steps = [Step::from_str("R10").unwrap(),
                      Step::from_str("R10").unwrap(),
                      Step::from_str("R10").unwrap()];
-        let result = distance_to_endpoint(&steps);
+        let result = distance_to_endpoint(&steps, ORIGIN, Direction::North).unwrap();
         assert_eq!(10, result);
     }
 
+    #[test]
+    fn distance_to_endpoint_overflow() {
+        let steps = parse_input("R2147483647, L1, R1").unwrap();
+        let result = distance_to_endpoint(&steps, ORIGIN, Direction::North);
+        assert!(matches!(result, Err(Error::Overflow(_))));
+        let steps = parse_input("R2147483647, R2147483647").unwrap();
+        let result = distance_to_endpoint(&steps, ORIGIN, Direction::North);
+        assert_eq!(u32::MAX - 1, result.unwrap());
+    }
+
     #[test]
     fn distance_to_first_path_overlap_no_crossing() {
         let step = Step::from_str("R1").unwrap();
-        let result = distance_to_first_path_overlap(&[step]);
-        assert!(result.is_err());
+        let result = distance_to_first_path_overlap(&[step], ORIGIN, Direction::North);
+        assert!(matches!(result, Err(Error::NoSolution(_))));
+    }
+
+    #[test]
+    fn distance_to_first_path_overlap_negative() {
+        let steps = parse_input("R-8, R-4, R-4, R-8").unwrap();
+        let result = distance_to_first_path_overlap(&steps, ORIGIN, Direction::North).unwrap();
+        assert_eq!(4, result);
+    }
+
+    #[test]
+    fn distance_to_first_path_overlap_huge() {
+        let steps = parse_input("R2000000000, R4, R4, R2000000000").unwrap();
+        let result = distance_to_first_path_overlap(&steps, ORIGIN, Direction::North).unwrap();
+        assert_eq!(1999999996, result);
+    }
+
+    #[test]
+    fn distance_to_first_path_overlap_back_to_start() {
+        let steps = parse_input("R2, R2, R2, R2, R1").unwrap();
+        let result = distance_to_first_path_overlap(&steps, Position(5, 5), Direction::North);
+        assert_eq!(11, result.unwrap());
+    }
+
+    #[test]
+    fn distance_to_first_path_overlap_turning_back() {
+        // A step of zero turns without moving, so the next step goes back the same way.
+        let steps = parse_input("R6, R0, R3").unwrap();
+        let result = distance_to_first_path_overlap(&steps, ORIGIN, Direction::North).unwrap();
+        assert_eq!(5, result);
+    }
+
+    #[test]
+    fn distance_to_first_path_overlap_first_along_segment() {
+        // The last step crosses the third step at (2, 2) before it crosses the first at (2, 0).
+        let steps = parse_input("R4, L2, L3, R2, R1, R6").unwrap();
+        let result = distance_to_first_path_overlap(&steps, ORIGIN, Direction::North).unwrap();
+        assert_eq!(4, result);
     }
 
     #[test]
@@ -115,7 +242,7 @@ mod tests {
                      Step::from_str("R4").unwrap(),
                      Step::from_str("R4").unwrap(),
                      Step::from_str("R8").unwrap()];
-        let result = distance_to_first_path_overlap(&steps).unwrap();
+        let result = distance_to_first_path_overlap(&steps, ORIGIN, Direction::North).unwrap();
         assert_eq!(4, result);
     }
 }
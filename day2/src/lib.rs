@@ -1,190 +1,299 @@
 extern crate base;
 
-use base::{Part, ProblemSolver};
-use base::geo::{Position, Direction};
+use base::{input, DynProblemSolver, Error, ParamKind, ParamSpec, Params, Part, ProblemSolver,
+           ParseError, Registry, Solution};
+use base::geo::{Direction, Grid, Position};
 
+use std::io::BufRead;
 use std::str::FromStr;
 
-pub fn get_solver() -> Box<ProblemSolver> {
-    Box::new(Day2)
+pub fn get_solver() -> Box<dyn DynProblemSolver> {
+    Box::new(Day2::default())
 }
 
-struct Day2;
+/// Registers the solver for this day.
+pub fn register(registry: &mut Registry) {
+    registry.register(2016, 2, env!("CARGO_PKG_NAME"), get_solver);
+}
+
+static PARAMS: [ParamSpec; 1] = [ParamSpec {
+                                     name: "keypad",
+                                     kind: ParamKind::Choice(&["by-part", "sane", "crazy"]),
+                                     default: "by-part",
+                                     help: "The keypad to enter the code on. by-part uses the \
+                                            sane keypad for part one and the crazy one for part \
+                                            two.",
+                                 }];
+
+/// The keypad to enter the code on.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+enum KeyPadChoice {
+    /// The sane keypad for part one and the crazy keypad for part two, as in the puzzle.
+    #[default]
+    ByPart,
+    Sane,
+    Crazy,
+}
+
+#[derive(Default)]
+struct Day2 {
+    keypad: KeyPadChoice,
+}
 
 impl ProblemSolver for Day2 {
-    fn solve(&self, part: Part, input: String) -> Result<String, String> {
-        let movements = parse_input(input)?;
-        match part {
-            Part::One => enter_code(movements, KeyPad::new(SaneKeyPadPositions)),
-            Part::Two => enter_code(movements, KeyPad::new(CrazyKeyPadPositions)),
+    type Input = Vec<Vec<Direction>>;
+
+    fn title(&self) -> &'static str {
+        "Bathroom Security"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &PARAMS
+    }
+
+    fn configure(&mut self, params: &Params) -> Result<(), Error> {
+        self.keypad = match params.choice("keypad") {
+            "sane" => KeyPadChoice::Sane,
+            "crazy" => KeyPadChoice::Crazy,
+            _ => KeyPadChoice::ByPart,
+        };
+        Ok(())
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        parse_input(input).map_err(|mut errors| Error::from(errors.remove(0)))
+    }
+
+    fn parse_reader(&self, reader: &mut dyn BufRead) -> Result<Self::Input, Error> {
+        let mut movements = vec![];
+        for line in input::spanned_lines(reader) {
+            let (line, line_span) = line?;
+            let key_movements =
+                parse_line(&line).map_err(|mut errors| errors.remove(0).within_span(line_span))?;
+            movements.push(key_movements);
+        }
+        Ok(movements)
+    }
+
+    fn check(&self, input: &str) -> Vec<ParseError> {
+        parse_input(input).err().unwrap_or_default()
+    }
+
+    fn solve(&self, part: Part, movements: &Self::Input) -> Result<Solution, Error> {
+        match (self.keypad, part) {
+            (KeyPadChoice::Sane, _) |
+            (KeyPadChoice::ByPart, Part::One) => {
+                enter_code(movements, KeyPad::new(SANE_KEYPAD))
+            }
+            (KeyPadChoice::Crazy, _) |
+            (KeyPadChoice::ByPart, Part::Two) => {
+                enter_code(movements, KeyPad::new(CRAZY_KEYPAD))
+            }
         }
     }
 }
 
-fn parse_input(input: String) -> Result<Vec<Vec<Direction>>, String> {
+/// Parses the movements for entering each key of the code, one key per line. Returns every
+/// invalid movement, with spans relative to `input`.
+fn parse_input(input: &str) -> Result<Vec<Vec<Direction>>, Vec<ParseError>> {
     let mut movements = vec![];
-    for line in input.split_terminator("\n") {
-        let mut key_movements = vec![];
-        for c in line.chars() {
-            let direction = Direction::from_str(&c.to_string())?;
-            key_movements.push(direction);
+    let mut errors = vec![];
+    for line in input.split_terminator('\n') {
+        match parse_line(line) {
+            Ok(key_movements) => movements.push(key_movements),
+            Err(line_errors) => {
+                errors.extend(line_errors.into_iter().map(|e| e.within(input, line)))
+            }
+        }
+    }
+    if errors.is_empty() { Ok(movements) } else { Err(errors) }
+}
+
+/// Parses the movements for entering one key of the code. Returns every invalid movement, with
+/// spans relative to `line`.
+fn parse_line(line: &str) -> Result<Vec<Direction>, Vec<ParseError>> {
+    let mut key_movements = vec![];
+    let mut errors = vec![];
+    for (i, c) in line.char_indices() {
+        let direction_str = &line[i..i + c.len_utf8()];
+        match Direction::from_str(direction_str) {
+            Ok(direction) => key_movements.push(direction),
+            Err(e) => errors.push(e.within(line, direction_str)),
         }
-        movements.push(key_movements);
     }
-    Ok(movements)
+    if errors.is_empty() { Ok(key_movements) } else { Err(errors) }
 }
 
-fn enter_code<P>(movements: Vec<Vec<Direction>>, mut keypad: KeyPad<P>) -> Result<String, String>
-    where P: KeyPadPositions
-{
-    let mut code = vec![];
+/// The keypad of part one, one row per line. A `.` is where there is no key.
+static SANE_KEYPAD: &str = concat!("123\n",
+                                   "456\n",
+                                   "789");
+
+/// The keypad of part two.
+static CRAZY_KEYPAD: &str = concat!("..1..\n",
+                                    ".234.\n",
+                                    "56789\n",
+                                    ".ABC.\n",
+                                    "..D..");
+
+fn enter_code(movements: &[Vec<Direction>], mut keypad: KeyPad) -> Result<Solution, Error> {
+    let mut code = String::new();
     for one_digit_movements in movements {
         for movement in one_digit_movements {
-            keypad.walk(&movement);
+            keypad.walk(movement);
         }
         code.push(keypad.key());
     }
-    Ok(code.join(""))
+    Ok(Solution::from(code))
 }
 
-struct KeyPad<P: KeyPadPositions> {
-    positions: P,
+struct KeyPad {
+    keys: Grid<Option<char>>,
     active_position: Position,
 }
 
-impl<P: KeyPadPositions> KeyPad<P> {
-    pub fn new(positions: P) -> Self {
+impl KeyPad {
+    /// Creates a keypad from its layout, with the finger on key 5. Panics if the layout has no
+    /// key 5.
+    pub fn new(layout: &str) -> Self {
+        let keys = Grid::parse(layout, |c| Some(if c == '.' { None } else { Some(c) }))
+            .expect("Invalid keypad layout");
+        let active_position = keys.find(|&key| key == Some('5')).expect("No key 5 on the keypad");
         KeyPad {
-            positions: positions,
-            active_position: P::initial_position(),
+            keys,
+            active_position,
         }
     }
 
     pub fn walk(&mut self, direction: &Direction) {
-        let mut new_active_position = self.active_position.clone();
-        new_active_position.walk(direction, 1);
-        if self.positions.key(&new_active_position).is_ok() {
+        let new_active_position = self.active_position + direction.to_position_representation();
+        if let Some(&Some(_)) = self.keys.get(new_active_position) {
             self.active_position = new_active_position;
         }
     }
 
-    pub fn key(&self) -> String {
-        self.positions.key(&self.active_position).unwrap()
-    }
-
-    pub fn reset(&mut self) {
-        self.active_position = P::initial_position();
+    pub fn key(&self) -> char {
+        self.keys[self.active_position].unwrap()
     }
 }
 
-trait KeyPadPositions {
-    fn key(&self, position: &Position) -> Result<String, ()>;
-    fn initial_position() -> Position;
-}
+#[cfg(test)]
+mod tests {
+    use base::{Error, Params, Part, ProblemSolver, Solution, Span};
+    use base::geo::Direction;
+    use super::{get_solver, Day2, KeyPad, CRAZY_KEYPAD, SANE_KEYPAD};
 
-struct SaneKeyPadPositions;
-
-impl KeyPadPositions for SaneKeyPadPositions {
-    fn key(&self, position: &Position) -> Result<String, ()> {
-        match *position {
-            Position(0, 2) => Ok(1.to_string()),
-            Position(1, 2) => Ok(2.to_string()),
-            Position(2, 2) => Ok(3.to_string()),
-            Position(0, 1) => Ok(4.to_string()),
-            Position(1, 1) => Ok(5.to_string()),
-            Position(2, 1) => Ok(6.to_string()),
-            Position(0, 0) => Ok(7.to_string()),
-            Position(1, 0) => Ok(8.to_string()),
-            Position(2, 0) => Ok(9.to_string()),
-            _ => Err(()),
-        }
+    #[test]
+    fn parse_reader_same_as_parse() {
+        let input = "ULL\nRRDDD\nLURDL\nUUUUD\n";
+        let from_str = Day2::default().parse(input).unwrap();
+        let from_reader = Day2::default().parse_reader(&mut input.as_bytes()).unwrap();
+        assert_eq!(from_str, from_reader);
+        assert_eq!(4, from_str.len());
     }
 
-    fn initial_position() -> Position {
-        Position(1, 1)
+    #[test]
+    fn parse_reader_invalid_direction() {
+        assert!(Day2::default().parse_reader(&mut &b"UL\nUX\n"[..]).is_err());
     }
-}
-
-struct CrazyKeyPadPositions;
-
-impl KeyPadPositions for CrazyKeyPadPositions {
-    fn key(&self, position: &Position) -> Result<String, ()> {
-        match *position {
-            Position(0, 2) => Ok("1".to_owned()),
-
-            Position(-1, 1) => Ok("2".to_owned()),
-            Position(0, 1) => Ok("3".to_owned()),
-            Position(1, 1) => Ok("4".to_owned()),
 
-            Position(-2, 0) => Ok("5".to_owned()),
-            Position(-1, 0) => Ok("6".to_owned()),
-            Position(0, 0) => Ok("7".to_owned()),
-            Position(1, 0) => Ok("8".to_owned()),
-            Position(2, 0) => Ok("9".to_owned()),
-
-            Position(-1, -1) => Ok("A".to_owned()),
-            Position(0, -1) => Ok("B".to_owned()),
-            Position(1, -1) => Ok("C".to_owned()),
-
-            Position(0, -2) => Ok("D".to_owned()),
+    #[test]
+    fn parse_reader_error_span() {
+        let error = match Day2::default().parse_reader(&mut &b"UL\nUX\n"[..]) {
+            Err(Error::Parse(e)) => e,
+            _ => panic!("Expected a parse error"),
+        };
+        assert_eq!(Some(Span { start: 4, end: 5, line: 2, column: 2 }), error.span());
+    }
 
-            _ => Err(()),
-        }
+    #[test]
+    fn parse_reader_error_span_crlf() {
+        let error = match Day2::default().parse_reader(&mut &b"UL\r\nDD\r\nUX\r\n"[..]) {
+            Err(Error::Parse(e)) => e,
+            _ => panic!("Expected a parse error"),
+        };
+        assert_eq!(Some(Span { start: 9, end: 10, line: 3, column: 2 }), error.span());
     }
 
-    fn initial_position() -> Position {
-        Position(-2, 0)
+    #[test]
+    fn check_reports_every_error() {
+        let errors = Day2::default().check("UXL\nDDQ\nRR\n");
+        let spans: Vec<_> = errors.iter().map(|e| e.span().unwrap()).collect();
+        assert_eq!(vec![Span { start: 1, end: 2, line: 1, column: 2 },
+                        Span { start: 6, end: 7, line: 2, column: 3 }],
+                   spans);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use base::geo::Direction;
-    use super::{KeyPad, SaneKeyPadPositions};
+    #[test]
+    fn keypad_param() {
+        let example = "ULL\nRRDDD\nLURDL\nUUUUD\n";
+        let mut solver = get_solver();
+        let input = solver.parse(example).unwrap();
+        assert_eq!(Solution::from("1985"), solver.solve(Part::One, &input).unwrap());
+        assert_eq!(Solution::from("5DB3"), solver.solve(Part::Two, &input).unwrap());
+
+        let params = [("keypad".to_owned(), "crazy".to_owned())];
+        solver.configure(&Params::new(solver.params(), &params).unwrap()).unwrap();
+        assert_eq!(Solution::from("5DB3"), solver.solve(Part::One, &input).unwrap());
+    }
 
     #[test]
     fn keypad_new() {
-        let keypad = KeyPad::new(SaneKeyPadPositions);
-        assert_eq!("5", keypad.key());
+        let keypad = KeyPad::new(SANE_KEYPAD);
+        assert_eq!('5', keypad.key());
     }
 
     #[test]
     fn keypad_move() {
-        let mut keypad = KeyPad::new(SaneKeyPadPositions);
+        let mut keypad = KeyPad::new(SANE_KEYPAD);
         keypad.walk(&Direction::North);
-        assert_eq!("2", keypad.key());
+        assert_eq!('2', keypad.key());
     }
 
     #[test]
     fn keypad_move_too_far() {
-        let mut keypad = KeyPad::new(SaneKeyPadPositions);
+        let mut keypad = KeyPad::new(SANE_KEYPAD);
         keypad.walk(&Direction::North);
         keypad.walk(&Direction::East);
         keypad.walk(&Direction::East);
-        assert_eq!("3", keypad.key());
+        assert_eq!('3', keypad.key());
     }
 
     #[test]
     fn keypad_move_down_and_away() {
-        let mut keypad = KeyPad::new(SaneKeyPadPositions);
+        let mut keypad = KeyPad::new(SANE_KEYPAD);
         keypad.walk(&Direction::South);
-        assert_eq!("8", keypad.key());
+        assert_eq!('8', keypad.key());
         keypad.walk(&Direction::South);
-        assert_eq!("8", keypad.key());
+        assert_eq!('8', keypad.key());
         keypad.walk(&Direction::East);
-        assert_eq!("9", keypad.key());
+        assert_eq!('9', keypad.key());
         keypad.walk(&Direction::West);
         keypad.walk(&Direction::West);
-        assert_eq!("7", keypad.key());
+        assert_eq!('7', keypad.key());
         keypad.walk(&Direction::West);
-        assert_eq!("7", keypad.key());
+        assert_eq!('7', keypad.key());
     }
 
     #[test]
     fn keypad_move_to_start() {
-        let mut keypad = KeyPad::new(SaneKeyPadPositions);
+        let mut keypad = KeyPad::new(SANE_KEYPAD);
         keypad.walk(&Direction::North);
         keypad.walk(&Direction::South);
-        assert_eq!("5", keypad.key());
+        assert_eq!('5', keypad.key());
+    }
+
+    #[test]
+    fn crazy_keypad_edges() {
+        let mut keypad = KeyPad::new(CRAZY_KEYPAD);
+        keypad.walk(&Direction::North);
+        assert_eq!('5', keypad.key());
+        keypad.walk(&Direction::East);
+        keypad.walk(&Direction::North);
+        keypad.walk(&Direction::North);
+        assert_eq!('2', keypad.key());
+        keypad.walk(&Direction::East);
+        keypad.walk(&Direction::North);
+        assert_eq!('1', keypad.key());
     }
 }
@@ -1,57 +1,107 @@
 extern crate base;
 
-use base::{Part, ProblemSolver};
+use base::{input, DynProblemSolver, Error, Part, ProblemSolver, ParseError, Registry, Solution};
 
+use std::io::BufRead;
 use std::str::FromStr;
 
-pub fn get_solver() -> Box<ProblemSolver> {
-    Box::new(Day2)
+pub fn get_solver() -> Box<dyn DynProblemSolver> {
+    Box::new(Day3)
 }
 
-struct Day2;
+/// Registers the solver for this day.
+pub fn register(registry: &mut Registry) {
+    registry.register(2016, 3, env!("CARGO_PKG_NAME"), get_solver);
+}
+
+struct Day3;
+
+impl ProblemSolver for Day3 {
+    type Input = Vec<Vec<u32>>;
+
+    fn title(&self) -> &'static str {
+        "Squares With Three Sides"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        parse_input(input).map_err(|mut errors| Error::from(errors.remove(0)))
+    }
+
+    fn parse_reader(&self, reader: &mut dyn BufRead) -> Result<Self::Input, Error> {
+        let mut rows = vec![];
+        for line in input::spanned_lines(reader) {
+            let (line, line_span) = line?;
+            let row =
+                parse_row(&line).map_err(|mut errors| errors.remove(0).within_span(line_span))?;
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+
+    fn check(&self, input: &str) -> Vec<ParseError> {
+        parse_input(input).err().unwrap_or_default()
+    }
 
-impl ProblemSolver for Day2 {
-    fn solve(&self, part: Part, input: String) -> Result<String, String> {
+    fn solve(&self, part: Part, rows: &Self::Input) -> Result<Solution, Error> {
         let triangles = match part {
-            Part::One => parse_triangle_rows(input)?,
-            Part::Two => parse_triangle_column(input)?,
+            Part::One => triangle_rows(rows),
+            Part::Two => triangle_columns(rows)?,
         };
         let num_valid = triangles.iter().filter(|t| t.is_valid()).count();
-        Ok(format!("{}", num_valid))
+        Ok(Solution::from(num_valid))
     }
 }
 
-fn parse_triangle_rows(input: String) -> Result<Vec<Triangle>, String> {
-    let mut triangles = vec![];
-    for line in input.lines().map(|line| line.split_whitespace()) {
-        let mut sides = vec![];
-        for side in line {
-            sides.push(u32::from_str(side).map_err(|e| e.to_string())?);
+/// Parses the input into rows of numbers, one row per line. Returns every invalid number, with
+/// spans relative to `input`.
+fn parse_input(input: &str) -> Result<Vec<Vec<u32>>, Vec<ParseError>> {
+    let mut rows = vec![];
+    let mut errors = vec![];
+    for line in input.lines() {
+        match parse_row(line) {
+            Ok(row) => rows.push(row),
+            Err(row_errors) => errors.extend(row_errors.into_iter().map(|e| e.within(input, line))),
         }
-        triangles.push(Triangle::new(sides));
     }
-    Ok(triangles)
+    if errors.is_empty() { Ok(rows) } else { Err(errors) }
 }
 
-fn parse_triangle_column(input: String) -> Result<Vec<Triangle>, String> {
-    let lines: Vec<String> = input.lines().map(|s| s.to_owned()).collect();
-    if lines.len() % 3 != 0 {
-        return Err("Number of lines must be divisible by 3".to_owned());
+/// Parses one row of whitespace separated numbers. Returns every invalid number, with spans
+/// relative to `line`.
+fn parse_row(line: &str) -> Result<Vec<u32>, Vec<ParseError>> {
+    let mut row = vec![];
+    let mut errors = vec![];
+    for number in line.split_whitespace() {
+        match u32::from_str(number) {
+            Ok(number) => row.push(number),
+            Err(e) => {
+                let error = ParseError::with_source(format!("Invalid number: {}", number), e);
+                errors.push(error.within(line, number));
+            }
+        }
+    }
+    if errors.is_empty() { Ok(row) } else { Err(errors) }
+}
+
+/// Reads every row as the sides of one triangle.
+fn triangle_rows(rows: &[Vec<u32>]) -> Vec<Triangle> {
+    rows.iter().map(|row| Triangle::new(row.clone())).collect()
+}
+
+/// Reads the sides of the triangles vertically, in groups of three rows.
+fn triangle_columns(rows: &[Vec<u32>]) -> Result<Vec<Triangle>, Error> {
+    if !rows.len().is_multiple_of(3) {
+        return Err(ParseError::new("Number of lines must be divisible by 3").into());
     }
     let mut triangles = vec![];
-    for three_lines in lines.chunks(3) {
-        let chunk_numbers: Vec<Vec<&str>> = three_lines.iter()
-            .map(|line| line.split_whitespace().collect())
-            .collect();
-        if chunk_numbers[0].len() != chunk_numbers[1].len() ||
-           chunk_numbers[0].len() != chunk_numbers[2].len() {
-            return Err("Lines must be of equal length".to_owned());
+    for three_rows in rows.chunks(3) {
+        if three_rows[0].len() != three_rows[1].len() ||
+           three_rows[0].len() != three_rows[2].len() {
+            return Err(ParseError::new("Lines must be of equal length").into());
         }
-        for i in 0..chunk_numbers[0].len() {
-            let sides = vec![u32::from_str(chunk_numbers[0][i]).unwrap(),
-                             u32::from_str(chunk_numbers[1][i]).unwrap(),
-                             u32::from_str(chunk_numbers[2][i]).unwrap()];
-            triangles.push(Triangle::new(sides));
+        let columns = three_rows[0].iter().zip(&three_rows[1]).zip(&three_rows[2]);
+        for ((&a, &b), &c) in columns {
+            triangles.push(Triangle::new(vec![a, b, c]));
         }
     }
     Ok(triangles)
@@ -64,7 +114,7 @@ pub struct Triangle {
 
 impl Triangle {
     pub fn new(sides: Vec<u32>) -> Self {
-        Triangle { sides: sides }
+        Triangle { sides }
     }
 
     pub fn is_valid(&self) -> bool {
@@ -76,6 +126,63 @@ impl Triangle {
 
 #[cfg(test)]
 mod tests {
+    use base::{Error, ProblemSolver, Span};
+    use super::{parse_input, triangle_columns, triangle_rows, Day3, Triangle};
+
     #[test]
     fn it_works() {}
+
+    #[test]
+    fn parse_input_invalid_number() {
+        assert!(parse_input("1 2 x").is_err());
+    }
+
+    #[test]
+    fn parse_input_reports_every_error() {
+        let errors = parse_input("  1 -2 3\n 4 5 99999999999\n").unwrap_err();
+        let spans: Vec<_> = errors.iter().map(|e| e.span().unwrap()).collect();
+        assert_eq!(vec![Span { start: 4, end: 6, line: 1, column: 5 },
+                        Span { start: 14, end: 25, line: 2, column: 6 }],
+                   spans);
+    }
+
+    #[test]
+    fn parse_reader_same_as_parse() {
+        let input = "  5 10 25\n 3  4  5\n";
+        assert_eq!(Day3.parse(input).unwrap(),
+                   Day3.parse_reader(&mut input.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn parse_reader_error_span_crlf() {
+        let error = match Day3.parse_reader(&mut &b"  5 10 25\r\n 3  x  5\r\n"[..]) {
+            Err(Error::Parse(e)) => e,
+            _ => panic!("Expected a parse error"),
+        };
+        assert_eq!(Some(Span { start: 15, end: 16, line: 2, column: 5 }), error.span());
+    }
+
+    #[test]
+    fn triangle_rows_one_per_line() {
+        let rows = parse_input("  5 10 25\n 3  4  5\n").unwrap();
+        let triangles = triangle_rows(&rows);
+        assert_eq!(vec![Triangle::new(vec![5, 10, 25]), Triangle::new(vec![3, 4, 5])],
+                   triangles);
+        assert!(!triangles[0].is_valid());
+        assert!(triangles[1].is_valid());
+    }
+
+    #[test]
+    fn triangle_columns_reads_vertically() {
+        let rows = parse_input("1 2\n3 4\n5 6\n").unwrap();
+        let triangles = triangle_columns(&rows).unwrap();
+        assert_eq!(vec![Triangle::new(vec![1, 3, 5]), Triangle::new(vec![2, 4, 6])],
+                   triangles);
+    }
+
+    #[test]
+    fn triangle_columns_wrong_line_count() {
+        let rows = parse_input("1\n2\n").unwrap();
+        assert!(triangle_columns(&rows).is_err());
+    }
 }